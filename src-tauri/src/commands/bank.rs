@@ -0,0 +1,145 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    db::get_connection,
+    models::bank::{lookup_ifsc as lookup, validate_bank_details, BankAccount, IfscLookup},
+};
+
+/// Adds the account when it has no id, otherwise updates it. The first account becomes
+/// the default, and making another one the default takes it off the rest.
+#[command]
+pub fn save_bank_account(account: BankAccount) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let account = BankAccount {
+        bank_name: account.bank_name.trim().to_string(),
+        branch: account.branch.trim().to_string(),
+        account_name: account.account_name.trim().to_string(),
+        account_no: account.account_no.trim().to_string(),
+        ifsc_code: account.ifsc_code.trim().to_ascii_uppercase(),
+        ..account
+    };
+    if account.bank_name.is_empty() || account.account_name.is_empty() {
+        return Err("Bank name and account holder's name are required".to_string());
+    }
+    validate_bank_details(&account.details())?;
+    let label = match account.label.trim() {
+        "" => account.bank_name.clone(),
+        label => label.to_string(),
+    };
+
+    let others: i64 = tx
+        .query_row("SELECT COUNT(*) FROM bank_account WHERE id IS NOT ?1", params![account.id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let is_default = account.is_default || others == 0;
+    if is_default {
+        tx.execute("UPDATE bank_account SET is_default = 0 WHERE id IS NOT ?1", params![account.id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let id = match &account.id {
+        Some(id) => {
+            let updated = tx
+                .execute(
+                    "UPDATE bank_account SET label = ?1, bank_name = ?2, branch = ?3, account_name = ?4, account_no = ?5,
+                        ifsc_code = ?6, is_default = is_default OR ?7
+                    WHERE id = ?8",
+                    params![label, account.bank_name, account.branch, account.account_name, account.account_no, account.ifsc_code, is_default, id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Bank account {} not found", id));
+            }
+            id.clone()
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            tx.execute(
+                "INSERT INTO bank_account (id, label, bank_name, branch, account_name, account_no, ifsc_code, is_default, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    id, label, account.bank_name, account.branch, account.account_name, account.account_no, account.ifsc_code,
+                    is_default, Utc::now().to_rfc3339()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Removes the account. Invoices keep the details they were issued with, and the
+/// oldest remaining account takes over as the default if this one was.
+#[command]
+pub fn delete_bank_account(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn
+        .execute("DELETE FROM bank_account WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Bank account {} not found", id));
+    }
+    conn.execute(
+        "UPDATE bank_account SET is_default = 1
+        WHERE id = (SELECT id FROM bank_account ORDER BY created_at LIMIT 1)
+            AND NOT EXISTS (SELECT 1 FROM bank_account WHERE is_default)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok("Bank account deleted successfully.".to_string())
+}
+
+/// All accounts, the default first.
+#[command]
+pub fn get_bank_accounts() -> Result<Vec<BankAccount>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM bank_account ORDER BY is_default DESC, label")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], bank_account_from_row).map_err(|e| e.to_string())?;
+
+    let accounts: Result<Vec<BankAccount>, rusqlite::Error> = rows.collect();
+    accounts.map_err(|e| e.to_string())
+}
+
+/// Checks an IFSC and names its bank from the bundled list of bank codes, for filling in
+/// the form. Branches are not bundled, so the branch name is left to the user.
+#[command]
+pub fn lookup_ifsc(ifsc: String) -> Result<IfscLookup, String> {
+    lookup(&ifsc).ok_or_else(|| format!("{} is not a valid IFSC", ifsc.trim()))
+}
+
+pub(crate) fn load_bank_account(conn: &Connection, id: &str) -> Result<Option<BankAccount>, String> {
+    conn.query_row("SELECT * FROM bank_account WHERE id = ?1", params![id], bank_account_from_row)
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn default_bank_account(conn: &Connection) -> Result<Option<BankAccount>, String> {
+    conn.query_row("SELECT * FROM bank_account WHERE is_default LIMIT 1", [], bank_account_from_row)
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn bank_account_from_row(row: &Row) -> rusqlite::Result<BankAccount> {
+    Ok(BankAccount {
+        id: row.get("id")?,
+        label: row.get("label")?,
+        bank_name: row.get("bank_name")?,
+        branch: row.get("branch")?,
+        account_name: row.get("account_name")?,
+        account_no: row.get("account_no")?,
+        ifsc_code: row.get("ifsc_code")?,
+        is_default: row.get("is_default")?,
+    })
+}
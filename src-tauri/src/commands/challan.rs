@@ -0,0 +1,348 @@
+use chrono::{Local, Utc};
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
+use serde::Deserialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::{
+        invoice::{allocate_invoice_number, insert_invoice},
+        product::validate_items_against_catalog,
+        user::load_profile,
+    },
+    db::get_connection,
+    models::{
+        challan::{ChallanSeries, ChallanStatus, DeliveryChallan},
+        invoice::Invoice,
+        money::Percent,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ChallanFilter {
+    pub company_id: Option<String>,
+    pub status: Option<ChallanStatus>,
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+}
+
+/// Which open challans to bill and the tax to charge on them.
+#[derive(Deserialize, Debug)]
+pub struct ChallanInvoiceOptions {
+    pub challan_ids: Vec<String>,
+    pub invoice_date: Option<String>, // Defaults to today
+    pub cgst_percentage: Option<Percent>,
+    pub sgst_percentage: Option<Percent>,
+    pub igst_percentage: Option<Percent>,
+}
+
+/// Stores a challan under the next number in the challan series and returns that number.
+/// Challans do not move stock; the invoice raised against them does.
+#[command]
+pub fn create_delivery_challan(challan: DeliveryChallan) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    if challan.items.is_empty() {
+        return Err("A delivery challan needs at least one item".to_string());
+    }
+    if challan.items.iter().any(|item| item.quantity <= 0.0) {
+        return Err("Challan quantities must be greater than zero".to_string());
+    }
+    validate_items_against_catalog(&tx, &challan.items)?;
+
+    let items_json = serde_json::to_string(&challan.items).map_err(|e| e.to_string())?;
+    let transport_details_json = serde_json::to_string(&challan.transport_details).map_err(|e| e.to_string())?;
+
+    let company_id = match &challan.company_id {
+        Some(id) => Some(id.clone()),
+        None => tx
+            .query_row(
+                "SELECT id FROM company WHERE gst_number = ?1 LIMIT 1",
+                params![challan.recipient_gst_number],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?,
+    };
+
+    let challan_number = allocate_challan_number(&tx)?;
+    tx.execute(
+        "INSERT INTO delivery_challan (
+        id,
+        challan_number,
+        challan_date,
+        purpose,
+        status,
+        company_id,
+        issuer_name,
+        issuer_address,
+        issuer_gst_number,
+        issuer_phone,
+        recipient_name,
+        recipient_address,
+        recipient_gst_number,
+        recipient_phone,
+        transport_details_json,
+        items_json,
+        notes,
+        created_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        params![
+            Uuid::new_v4().to_string(),
+            challan_number,
+            challan.challan_date,
+            challan.purpose,
+            ChallanStatus::Open,
+            company_id,
+            challan.issuer_name,
+            challan.issuer_address,
+            challan.issuer_gst_number,
+            challan.issuer_phone,
+            challan.recipient_name,
+            challan.recipient_address,
+            challan.recipient_gst_number,
+            challan.recipient_phone,
+            transport_details_json,
+            items_json,
+            challan.notes,
+            Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(challan_number)
+}
+
+#[command]
+pub fn cancel_delivery_challan(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    let challan = fetch_challan(&conn, &id)?;
+
+    if challan.status != ChallanStatus::Open {
+        return Err(format!(
+            "Challan {} is {} and cannot be cancelled",
+            challan.challan_number,
+            challan.status.as_str()
+        ));
+    }
+
+    conn.execute(
+        "UPDATE delivery_challan SET status = ?1 WHERE id = ?2",
+        params![ChallanStatus::Cancelled, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(format!("Challan {} cancelled.", challan.challan_number))
+}
+
+#[command]
+pub fn search_delivery_challans(filter: ChallanFilter) -> Result<Vec<DeliveryChallan>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT * FROM delivery_challan WHERE 1=1".to_string();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(company_id) = filter.company_id {
+        values.push(company_id.into());
+        query.push_str(&format!(" AND company_id = ?{}", values.len()));
+    }
+    if let Some(status) = filter.status {
+        values.push(status.as_str().to_string().into());
+        query.push_str(&format!(" AND status = ?{}", values.len()));
+    }
+    if let Some(from_date) = filter.from_date {
+        values.push(from_date.into());
+        query.push_str(&format!(" AND challan_date >= ?{}", values.len()));
+    }
+    if let Some(to_date) = filter.to_date {
+        values.push(to_date.into());
+        query.push_str(&format!(" AND challan_date <= ?{}", values.len()));
+    }
+    query.push_str(" ORDER BY challan_date DESC, challan_number DESC");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), challan_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let challans: Result<Vec<DeliveryChallan>, rusqlite::Error> = rows.collect();
+    challans.map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_challan_series() -> Result<ChallanSeries, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    load_challan_series(&conn)
+}
+
+#[command]
+pub fn update_challan_series(series: ChallanSeries) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    if series.prefix.trim().is_empty() {
+        return Err("Challan prefix cannot be empty".to_string());
+    }
+    conn.execute(
+        "UPDATE document_series SET prefix = ?1, next_number = ?2 WHERE document = 'delivery_challan'",
+        params![series.prefix.trim(), series.next_number],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok("Challan series updated.".to_string())
+}
+
+/// Raises one tax invoice for the goods on one or more open challans of the same client.
+/// Items and quantities are carried over line by line at the challan rates, the invoice
+/// is numbered from the profile's invoice series, and the challans are marked invoiced.
+/// Returns the new invoice id.
+#[command]
+pub fn invoice_challans(options: ChallanInvoiceOptions) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    if options.challan_ids.is_empty() {
+        return Err("Select at least one challan to invoice".to_string());
+    }
+
+    // A challan picked twice would otherwise be billed twice
+    let mut challan_ids = options.challan_ids.clone();
+    challan_ids.sort();
+    challan_ids.dedup();
+
+    let mut challans = Vec::new();
+    for id in &challan_ids {
+        let challan = fetch_challan(&tx, id)?;
+        if challan.status != ChallanStatus::Open {
+            return Err(format!(
+                "Challan {} is {} and cannot be invoiced",
+                challan.challan_number,
+                challan.status.as_str()
+            ));
+        }
+        challans.push(challan);
+    }
+    challans.sort_by(|a, b| a.challan_date.cmp(&b.challan_date));
+
+    let first = &challans[0];
+    if challans.iter().any(|c| c.recipient_gst_number != first.recipient_gst_number) {
+        return Err("All challans on one invoice must be for the same client".to_string());
+    }
+
+    let items: Vec<_> = challans.iter().flat_map(|c| c.items.iter().cloned()).collect();
+
+    let profile = load_profile(&tx)?;
+    let invoice = Invoice {
+        issuer_name: first.issuer_name.clone(),
+        issuer_address: first.issuer_address.clone(),
+        issuer_gst_number: first.issuer_gst_number.clone(),
+        issuer_phone: first.issuer_phone.clone(),
+        issuer_email: Some(profile.email),
+        recipient_name: first.recipient_name.clone(),
+        recipient_address: first.recipient_address.clone(),
+        recipient_gst_number: first.recipient_gst_number.clone(),
+        recipient_phone: first.recipient_phone.clone(),
+        invoice_number: allocate_invoice_number(&tx)?,
+        invoice_date: options
+            .invoice_date
+            .unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string()),
+        cgst_percentage: options.cgst_percentage,
+        sgst_percentage: options.sgst_percentage,
+        igst_percentage: options.igst_percentage,
+        transport_details: first.transport_details.clone(),
+        items,
+        company_id: first.company_id.clone(),
+        ..Default::default()
+    };
+
+    let invoice_id = insert_invoice(&tx, &invoice)?;
+
+    for challan in &challans {
+        tx.execute(
+            "UPDATE delivery_challan SET status = ?1, invoice_id = ?2 WHERE id = ?3",
+            params![ChallanStatus::Invoiced, invoice_id, challan.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(invoice_id)
+}
+
+fn load_challan_series(conn: &Connection) -> Result<ChallanSeries, String> {
+    conn.query_row(
+        "SELECT prefix, next_number FROM document_series WHERE document = 'delivery_challan'",
+        [],
+        |row| Ok(ChallanSeries { prefix: row.get(0)?, next_number: row.get(1)? }),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Next `PREFIX-n` in the challan series, skipping numbers already on a challan.
+fn allocate_challan_number(conn: &Connection) -> Result<String, String> {
+    let series = load_challan_series(conn)?;
+
+    let mut number = series.next_number;
+    let challan_number = loop {
+        let candidate = format!("{}-{}", series.prefix, number);
+        let taken: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM delivery_challan WHERE challan_number = ?1)",
+                params![candidate],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !taken {
+            break candidate;
+        }
+        number += 1;
+    };
+
+    conn.execute(
+        "UPDATE document_series SET next_number = ?1 WHERE document = 'delivery_challan'",
+        params![number + 1],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(challan_number)
+}
+
+fn fetch_challan(conn: &Connection, id: &str) -> Result<DeliveryChallan, String> {
+    conn.query_row("SELECT * FROM delivery_challan WHERE id = ?1", params![id], challan_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Challan {} not found", id))
+}
+
+fn challan_from_row(row: &Row) -> rusqlite::Result<DeliveryChallan> {
+    let items_json: String = row.get("items_json")?;
+    let transport_details_json: String = row.get("transport_details_json")?;
+    let items = serde_json::from_str(&items_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    let transport_details = serde_json::from_str(&transport_details_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+
+    Ok(DeliveryChallan {
+        id: row.get("id")?,
+        challan_number: row.get("challan_number")?,
+        challan_date: row.get("challan_date")?,
+        purpose: row.get("purpose")?,
+        status: row.get("status")?,
+        company_id: row.get("company_id")?,
+        issuer_name: row.get("issuer_name")?,
+        issuer_address: row.get("issuer_address")?,
+        issuer_gst_number: row.get("issuer_gst_number")?,
+        issuer_phone: row.get("issuer_phone")?,
+        recipient_name: row.get("recipient_name")?,
+        recipient_address: row.get("recipient_address")?,
+        recipient_gst_number: row.get("recipient_gst_number")?,
+        recipient_phone: row.get("recipient_phone")?,
+        transport_details,
+        items,
+        notes: row.get("notes")?,
+        invoice_id: row.get("invoice_id")?,
+    })
+}
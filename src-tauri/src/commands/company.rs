@@ -1,7 +1,7 @@
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use tauri::command;
 use uuid::Uuid;
-use crate::{db::get_connection, models::company::Company};
+use crate::{db::get_connection, models::{address::Address, company::{AddressKind, Company, CompanyAddress, CompanyContact}}};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct CompanyFilter {
@@ -15,18 +15,39 @@ pub struct CompanyFilter {
 pub fn add_company(company: Company) -> Result<String, String> {
     let conn = get_connection().map_err(|e| e.to_string())?;
 
+    // Screens that still send one block of text get it kept as the first line
+    let (address, details) = match &company.address_details {
+        Some(details) => {
+            let details = details.normalized();
+            details.validate()?;
+            (details.to_string(), details)
+        }
+        None => (
+            company.address.clone(),
+            Address::from_legacy(&company.address, &company.pincode, &company.gst_number),
+        ),
+    };
+
     // Insert the company into the database
     conn.execute(
-        "INSERT INTO company (id, name, address, pincode, gst_number, phone, email, owner_name) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO company (id, name, address, pincode, gst_number, phone, email, owner_name, payment_terms_days,
+            address_line1, address_line2, city, district, state_code)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
         params![
             Uuid::new_v4().to_string(),
             company.name,
-            company.address,
-            company.pincode,
+            address,
+            details.pincode,
             company.gst_number,
             company.phone,
             company.email,
-            company.owner_name
+            company.owner_name,
+            company.payment_terms_days,
+            details.line1,
+            details.line2,
+            details.city,
+            details.district,
+            details.state_code
         ],
     )
     .map_err(|e| e.to_string())?;
@@ -72,23 +93,328 @@ pub fn search_company(filter: CompanyFilter) -> Result<Vec<Company>, String> {
 
     let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
     let rows = stmt
-        .query_map(params![value], |row| {
-
-            Ok(Company {
-                id: row.get("id")?,
-                name: row.get("name")?,
-                owner_name: row.get("owner_name")?,
-                address: row.get("address")?,
-                pincode: row.get("pincode")?,
-                gst_number: row.get("gst_number")?,
-                phone: row.get("phone")?,
-                email: row.get("email")?,
-                
-            })
-        })
+        .query_map(params![value], company_from_row)
         .map_err(|e| e.to_string())?;
 
     let companies: Result<Vec<Company>, rusqlite::Error> = rows.collect();
 
     companies.map_err(|e| e.to_string())
 }
+
+/// Adds an address to a client's address book, or replaces the one with the same id.
+/// Marking it the default takes that over from the client's other addresses of the
+/// same kind. Returns the address id.
+#[command]
+pub fn save_company_address(address: CompanyAddress) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let label = address.label.trim();
+    if label.is_empty() {
+        return Err("Address label is required".to_string());
+    }
+    let details = address.address.normalized();
+    details.validate()?;
+    let gst_number = address.gst_number.as_deref().map(str::trim).filter(|gst| !gst.is_empty());
+
+    if address.is_default {
+        let mut stmt = tx
+            .prepare("SELECT id, kind FROM company_address WHERE company_id = ?1 AND is_default AND id IS NOT ?2")
+            .map_err(|e| e.to_string())?;
+        let others = stmt
+            .query_map(params![address.company_id, address.id], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<(String, AddressKind)>, rusqlite::Error>>()
+            .map_err(|e| e.to_string())?;
+        for (id, kind) in others {
+            let overlaps = (kind.for_billing() && address.kind.for_billing()) || (kind.for_shipping() && address.kind.for_shipping());
+            if overlaps {
+                tx.execute("UPDATE company_address SET is_default = 0 WHERE id = ?1", params![id])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    let id = match &address.id {
+        Some(id) => {
+            let updated = tx
+                .execute(
+                    "UPDATE company_address SET label = ?1, address_line1 = ?2, address_line2 = ?3, city = ?4, district = ?5,
+                        state_code = ?6, pincode = ?7, gst_number = ?8, kind = ?9, is_default = ?10
+                    WHERE id = ?11 AND company_id = ?12",
+                    params![
+                        label, details.line1, details.line2, details.city, details.district, details.state_code,
+                        details.pincode, gst_number, address.kind, address.is_default, id, address.company_id
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Address {} not found", id));
+            }
+            id.clone()
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            tx.execute(
+                "INSERT INTO company_address (id, company_id, label, address_line1, address_line2, city, district,
+                    state_code, pincode, gst_number, kind, is_default)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    id, address.company_id, label, details.line1, details.line2, details.city, details.district,
+                    details.state_code, details.pincode, gst_number, address.kind, address.is_default
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[command]
+pub fn delete_company_address(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn
+        .execute("DELETE FROM company_address WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    if deleted == 0 {
+        return Err(format!("Address {} not found", id));
+    }
+    Ok("Address deleted.".to_string())
+}
+
+/// A client's address book, defaults first.
+#[command]
+pub fn get_company_addresses(company_id: String) -> Result<Vec<CompanyAddress>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM company_address WHERE company_id = ?1 ORDER BY is_default DESC, label")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![company_id], company_address_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let addresses: Result<Vec<CompanyAddress>, rusqlite::Error> = rows.collect();
+    addresses.map_err(|e| e.to_string())
+}
+
+/// An address book entry, checked to belong to `company_id` when that is known.
+pub(crate) fn load_address_book_entry(conn: &Connection, id: &str, company_id: Option<&str>) -> Result<CompanyAddress, String> {
+    let entry = conn
+        .query_row("SELECT * FROM company_address WHERE id = ?1", params![id], company_address_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Address {} not found", id))?;
+
+    if company_id.is_some_and(|company_id| company_id != entry.company_id) {
+        return Err(format!("Address {} belongs to another client", entry.label));
+    }
+    Ok(entry)
+}
+
+/// The client's default address for billing or for shipping, if one is marked.
+pub(crate) fn default_address_book_entry(conn: &Connection, company_id: &str, shipping: bool) -> Result<Option<CompanyAddress>, String> {
+    let kind = if shipping { AddressKind::Shipping } else { AddressKind::Billing };
+    conn.query_row(
+        "SELECT * FROM company_address WHERE company_id = ?1 AND is_default AND kind IN (?2, 'both') LIMIT 1",
+        params![company_id, kind],
+        company_address_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Adds a contact person to a client, or replaces the one with the same id. Returns the
+/// contact id.
+#[command]
+pub fn save_company_contact(contact: CompanyContact) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let name = contact.name.trim();
+    if name.is_empty() {
+        return Err("Contact name is required".to_string());
+    }
+    let optional = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    let (role, phone, email) = (optional(&contact.role), optional(&contact.phone), optional(&contact.email));
+    if phone.is_none() && email.is_none() {
+        return Err(format!("{} needs a phone number or an email address", name));
+    }
+    if let Some(email) = &email {
+        if !is_email(email) {
+            return Err(format!("{} is not a valid email address", email));
+        }
+    }
+    if (contact.receives_invoices || contact.receives_reminders) && email.is_none() {
+        return Err(format!("{} needs an email address to be sent invoices or reminders", name));
+    }
+
+    match &contact.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE company_contact SET name = ?1, role = ?2, phone = ?3, email = ?4, receives_invoices = ?5,
+                        receives_reminders = ?6
+                    WHERE id = ?7 AND company_id = ?8",
+                    params![name, role, phone, email, contact.receives_invoices, contact.receives_reminders, id, contact.company_id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Contact {} not found", id));
+            }
+            Ok(id.clone())
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO company_contact (id, company_id, name, role, phone, email, receives_invoices, receives_reminders)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![id, contact.company_id, name, role, phone, email, contact.receives_invoices, contact.receives_reminders],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+    }
+}
+
+#[command]
+pub fn delete_company_contact(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn
+        .execute("DELETE FROM company_contact WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    if deleted == 0 {
+        return Err(format!("Contact {} not found", id));
+    }
+    Ok("Contact deleted.".to_string())
+}
+
+#[command]
+pub fn get_company_contacts(company_id: String) -> Result<Vec<CompanyContact>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM company_contact WHERE company_id = ?1 ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![company_id], company_contact_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let contacts: Result<Vec<CompanyContact>, rusqlite::Error> = rows.collect();
+    contacts.map_err(|e| e.to_string())
+}
+
+/// Who a client's invoices (or, with `reminders`, payment reminders) get emailed to, for
+/// pre-filling the send dialog.
+#[command]
+pub fn get_contact_emails(company_id: String, reminders: bool) -> Result<Vec<String>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    contact_emails(&conn, &company_id, reminders)
+}
+
+/// Email addresses to send a client's invoices (or payment reminders) to: the contacts
+/// marked for them, or the client's own email when none are.
+pub(crate) fn contact_emails(conn: &Connection, company_id: &str, reminders: bool) -> Result<Vec<String>, String> {
+    let column = if reminders { "receives_reminders" } else { "receives_invoices" };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT email FROM company_contact WHERE company_id = ?1 AND {} AND email IS NOT NULL ORDER BY name",
+            column
+        ))
+        .map_err(|e| e.to_string())?;
+    let emails = stmt
+        .query_map(params![company_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+    if !emails.is_empty() {
+        return Ok(emails);
+    }
+
+    let company_email: Option<String> = conn
+        .query_row("SELECT email FROM company WHERE id = ?1", params![company_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    Ok(company_email.filter(|email| is_email(email)).into_iter().collect())
+}
+
+/// Loose check for something@domain.tld, enough to catch typos.
+pub(crate) fn is_email(value: &str) -> bool {
+    match value.trim().split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty() && !domain.contains('@') && domain.split('.').count() > 1 && domain.split('.').all(|part| !part.is_empty())
+        }
+        None => false,
+    }
+}
+
+/// The client's structured address, if there is such a client.
+pub(crate) fn load_company_address(conn: &Connection, id: &str) -> Result<Option<Address>, String> {
+    conn.query_row("SELECT * FROM company WHERE id = ?1", params![id], company_from_row)
+        .optional()
+        .map(|company| company.and_then(|company| company.address_details))
+        .map_err(|e| e.to_string())
+}
+
+fn company_address_from_row(row: &Row) -> rusqlite::Result<CompanyAddress> {
+    Ok(CompanyAddress {
+        id: row.get("id")?,
+        company_id: row.get("company_id")?,
+        label: row.get("label")?,
+        address: Address {
+            line1: row.get("address_line1")?,
+            line2: row.get("address_line2")?,
+            city: row.get("city")?,
+            district: row.get("district")?,
+            state_code: row.get("state_code")?,
+            pincode: row.get("pincode")?,
+        },
+        gst_number: row.get("gst_number")?,
+        kind: row.get("kind")?,
+        is_default: row.get("is_default")?,
+    })
+}
+
+fn company_contact_from_row(row: &Row) -> rusqlite::Result<CompanyContact> {
+    Ok(CompanyContact {
+        id: row.get("id")?,
+        company_id: row.get("company_id")?,
+        name: row.get("name")?,
+        role: row.get("role")?,
+        phone: row.get("phone")?,
+        email: row.get("email")?,
+        receives_invoices: row.get("receives_invoices")?,
+        receives_reminders: row.get("receives_reminders")?,
+    })
+}
+
+fn company_from_row(row: &Row) -> rusqlite::Result<Company> {
+    let pincode: String = row.get("pincode")?;
+
+    Ok(Company {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        owner_name: row.get("owner_name")?,
+        address: row.get("address")?,
+        address_details: Some(Address {
+            line1: row.get("address_line1")?,
+            line2: row.get("address_line2")?,
+            city: row.get("city")?,
+            district: row.get("district")?,
+            state_code: row.get("state_code")?,
+            pincode: pincode.clone(),
+        }),
+        pincode,
+        gst_number: row.get("gst_number")?,
+        phone: row.get("phone")?,
+        email: row.get("email")?,
+        payment_terms_days: row.get("payment_terms_days")?,
+    })
+}
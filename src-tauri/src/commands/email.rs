@@ -0,0 +1,394 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::{
+        company::{contact_emails, is_email},
+        invoice::{fetch_invoices, invoice_rounding_rules},
+        payment::invoice_balance,
+        upi::invoice_upi_uri,
+    },
+    db::get_connection,
+    models::{
+        email::{EmailLogEntry, EmailSettings, EmailStatus, SmtpSecurity, DEFAULT_BODY_TEMPLATE, DEFAULT_SUBJECT_TEMPLATE},
+        invoice::{Invoice, InvoiceStatus, InvoiceTotals},
+    },
+    utils::{
+        amount::{amount_in_words, format_indian},
+        invoice_pdf::invoice_pdf,
+        table::format_date,
+    },
+};
+
+#[command]
+pub fn get_email_settings() -> Result<Option<EmailSettings>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    load_email_settings(&conn)
+}
+
+/// Saves the mail server settings. An empty password keeps the one already saved.
+/// The password is stored as is in the user's database, next to the invoices it
+/// protects, rather than in the OS keyring, which not every Linux desktop provides.
+#[command]
+pub fn save_email_settings(settings: EmailSettings) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    if settings.host.trim().is_empty() || settings.port == 0 {
+        return Err("Mail server host and port are required".to_string());
+    }
+    if !is_email(&settings.from_email) {
+        return Err(format!("{} is not a valid email address", settings.from_email));
+    }
+    let password = settings.password.as_deref().filter(|password| !password.is_empty());
+
+    conn.execute(
+        "INSERT INTO email_settings (id, host, port, security, username, password, from_name, from_email,
+            subject_template, body_template, updated_at)
+        VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ON CONFLICT (id) DO UPDATE SET host = ?1, port = ?2, security = ?3, username = ?4,
+            password = COALESCE(?5, password), from_name = ?6, from_email = ?7, subject_template = ?8,
+            body_template = ?9, updated_at = ?10",
+        params![
+            settings.host.trim(),
+            settings.port,
+            settings.security,
+            settings.username.as_deref().filter(|username| !username.is_empty()),
+            password,
+            settings.from_name.trim(),
+            settings.from_email.trim(),
+            settings.subject_template.as_deref().filter(|template| !template.trim().is_empty()),
+            settings.body_template.as_deref().filter(|template| !template.trim().is_empty()),
+            Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok("Email settings saved.".to_string())
+}
+
+/// Emails the invoice as a PDF using the saved server and templates. Without `recipients`
+/// it goes to the client's contacts marked for invoices, or else the invoice's email.
+/// Every attempt is logged against the invoice, failures included. Sending runs on a
+/// blocking thread so a slow mail server doesn't hold up the window.
+#[command]
+pub async fn send_invoice_email(invoice_id: String, recipients: Option<Vec<String>>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || email_invoice(invoice_id, recipients))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn email_invoice(invoice_id: String, recipients: Option<Vec<String>>) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let settings = load_email_settings(&conn)?.ok_or("Set up the mail server before sending invoices")?;
+    let invoice = fetch_invoices(&conn, "SELECT * FROM invoice WHERE id = ?1", &[invoice_id.clone().into()])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+    if invoice.status == InvoiceStatus::Cancelled {
+        return Err(format!("Invoice {} is cancelled", invoice.invoice_number));
+    }
+
+    let recipients = match recipients.filter(|recipients| !recipients.is_empty()) {
+        Some(recipients) => recipients,
+        None => invoice_recipients(&conn, &invoice, false)?,
+    };
+    if recipients.is_empty() {
+        return Err(format!("No email address to send {} to", invoice.invoice_number));
+    }
+    if let Some(bad) = recipients.iter().find(|email| !is_email(email)) {
+        return Err(format!("{} is not a valid email address", bad));
+    }
+
+    let totals = invoice.totals(&invoice_rounding_rules(&conn, &invoice)?);
+    let placeholders = invoice_placeholders(&invoice, &totals);
+    let subject = render_template(settings.subject_template.as_deref().unwrap_or(DEFAULT_SUBJECT_TEMPLATE), &placeholders);
+    let body = render_template(settings.body_template.as_deref().unwrap_or(DEFAULT_BODY_TEMPLATE), &placeholders);
+    let upi_uri = invoice_upi_uri(&conn, &invoice, invoice_balance(&conn, &invoice_id)?.outstanding)?;
+    let attachment = (format!("{}.pdf", invoice.invoice_number.replace(['/', '\\'], "-")), invoice_pdf(&invoice, &totals, upi_uri.as_deref())?);
+
+    let result = send_email(&settings, &recipients, &subject, &body, Some(attachment));
+    let (status, error) = match &result {
+        Ok(()) => (EmailStatus::Sent, None),
+        Err(e) => (EmailStatus::Failed, Some(e.clone())),
+    };
+    conn.execute(
+        "INSERT INTO email_log (id, invoice_id, recipients, subject, status, error, sent_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![Uuid::new_v4().to_string(), invoice_id, recipients.join(", "), subject, status, error, Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    result.map(|()| format!("{} sent to {}", invoice.invoice_number, recipients.join(", ")))
+}
+
+/// Emails sent (or attempted) for an invoice, latest first.
+#[command]
+pub fn get_email_log(invoice_id: String) -> Result<Vec<EmailLogEntry>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM email_log WHERE invoice_id = ?1 ORDER BY sent_at DESC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![invoice_id], email_log_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let entries: Result<Vec<EmailLogEntry>, rusqlite::Error> = rows.collect();
+    entries.map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_email_settings(conn: &Connection) -> Result<Option<EmailSettings>, String> {
+    conn.query_row("SELECT * FROM email_settings WHERE id = 1", [], |row| {
+        Ok(EmailSettings {
+            host: row.get("host")?,
+            port: row.get("port")?,
+            security: row.get("security")?,
+            username: row.get("username")?,
+            password: row.get("password")?,
+            from_name: row.get("from_name")?,
+            from_email: row.get("from_email")?,
+            subject_template: row.get("subject_template")?,
+            body_template: row.get("body_template")?,
+        })
+    })
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// The client's contacts for invoices (or reminders), falling back to the email on the invoice.
+pub(crate) fn invoice_recipients(conn: &Connection, invoice: &Invoice, reminders: bool) -> Result<Vec<String>, String> {
+    let from_contacts = match &invoice.company_id {
+        Some(company_id) => contact_emails(conn, company_id, reminders)?,
+        None => Vec::new(),
+    };
+    if !from_contacts.is_empty() {
+        return Ok(from_contacts);
+    }
+    Ok(invoice.recipient_email.iter().map(|email| email.trim().to_string()).filter(|email| !email.is_empty()).collect())
+}
+
+/// Values for the template placeholders. Totals are in the invoice's currency, the amount
+/// in words is always in rupees and names the rupee figure for foreign currency invoices.
+pub(crate) fn invoice_placeholders(invoice: &Invoice, totals: &InvoiceTotals) -> Vec<(&'static str, String)> {
+    let currency = invoice.foreign_currency.as_ref().map_or("Rs.", |currency| currency.code.as_str());
+    let in_words = match invoice.foreign_currency {
+        Some(_) => format!(
+            "rupee equivalent Rs. {}, {}",
+            format_indian(totals.total_inr.to_rupees()),
+            amount_in_words(totals.total_inr)
+        ),
+        None => amount_in_words(totals.total_inr),
+    };
+    vec![
+        ("invoice_number", invoice.invoice_number.clone()),
+        ("invoice_date", format_date(&invoice.invoice_date)),
+        ("document_type", invoice.document_type.label().to_string()),
+        ("total", format!("{} {}", currency, format_indian(totals.total.to_rupees()))),
+        ("amount_in_words", in_words),
+        ("recipient_name", invoice.recipient_name.clone()),
+        ("issuer_name", invoice.issuer_name.clone()),
+    ]
+}
+
+/// Replaces each `{name}` with its value. Unknown placeholders are left as they are.
+pub(crate) fn render_template(template: &str, placeholders: &[(&str, String)]) -> String {
+    placeholders
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Sends a plain text email, with an attachment if given, through the configured server.
+pub(crate) fn send_email(
+    settings: &EmailSettings,
+    recipients: &[String],
+    subject: &str,
+    body: &str,
+    attachment: Option<(String, Vec<u8>)>,
+) -> Result<(), String> {
+    let from_email = settings.from_email.parse().map_err(|e| format!("From address: {}", e))?;
+    let mut builder = Message::builder()
+        .from(Mailbox::new(Some(settings.from_name.clone()), from_email))
+        .subject(subject);
+    for recipient in recipients {
+        builder = builder.to(recipient.parse().map_err(|e| format!("{}: {}", recipient, e))?);
+    }
+
+    let text = SinglePart::plain(body.to_string());
+    let message = match attachment {
+        Some((filename, bytes)) => {
+            let pdf = ContentType::parse("application/pdf").map_err(|e| e.to_string())?;
+            builder.multipart(MultiPart::mixed().singlepart(text).singlepart(Attachment::new(filename).body(bytes, pdf)))
+        }
+        None => builder.singlepart(text),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let transport = match settings.security {
+        SmtpSecurity::None => SmtpTransport::builder_dangerous(&settings.host),
+        SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&settings.host).map_err(|e| e.to_string())?,
+        SmtpSecurity::Tls => SmtpTransport::relay(&settings.host).map_err(|e| e.to_string())?,
+    }
+    .port(settings.port)
+    .timeout(Some(Duration::from_secs(30)));
+    let transport = match (&settings.username, &settings.password) {
+        (Some(username), Some(password)) => transport.credentials(Credentials::new(username.clone(), password.clone())),
+        _ => transport,
+    }
+    .build();
+
+    transport.send(&message).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn email_log_from_row(row: &Row) -> rusqlite::Result<EmailLogEntry> {
+    let recipients: String = row.get("recipients")?;
+
+    Ok(EmailLogEntry {
+        id: row.get("id")?,
+        invoice_id: row.get("invoice_id")?,
+        recipients: recipients.split(", ").map(str::to_string).collect(),
+        subject: row.get("subject")?,
+        status: row.get("status")?,
+        error: row.get("error")?,
+        sent_at: row.get("sent_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    use super::*;
+    use crate::models::{
+        invoice::{ForeignCurrency, RoundingRules},
+        money::Money,
+    };
+
+    fn placeholder<'a>(placeholders: &'a [(&str, String)], name: &str) -> &'a str {
+        placeholders.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str()).unwrap()
+    }
+
+    #[test]
+    fn render_template_fills_known_placeholders() {
+        let placeholders = [("invoice_number", "INV-7".to_string()), ("total", "Rs. 1,180.00".to_string())];
+        assert_eq!(
+            render_template("{invoice_number} for {total}, {invoice_number} again {unknown}", &placeholders),
+            "INV-7 for Rs. 1,180.00, INV-7 again {unknown}"
+        );
+    }
+
+    #[test]
+    fn is_email_needs_a_user_and_a_dotted_domain() {
+        assert!(is_email("accounts@pune.in"));
+        assert!(is_email(" a.b@mail.example.com "));
+        for bad in ["", "accounts", "@pune.in", "a@pune", "a@pune.", "a@b@c.in", "a@.in"] {
+            assert!(!is_email(bad), "{} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn invoice_placeholders_name_the_rupee_figure_for_exports() {
+        let mut invoice = Invoice {
+            invoice_number: "E-1".to_string(),
+            invoice_date: "2025-01-31".to_string(),
+            recipient_name: "Acme Inc".to_string(),
+            issuer_name: "Me".to_string(),
+            ..Default::default()
+        };
+        let totals = InvoiceTotals {
+            total: Money::from_rupees(1000.0),
+            total_inr: Money::from_rupees(1000.0),
+            ..invoice.totals(&RoundingRules::default())
+        };
+        let placeholders = invoice_placeholders(&invoice, &totals);
+        assert_eq!(placeholder(&placeholders, "total"), "Rs. 1,000.00");
+        assert_eq!(placeholder(&placeholders, "amount_in_words"), "Rupees One Thousand Only");
+        assert_eq!(placeholder(&placeholders, "invoice_date"), "31-01-2025");
+
+        invoice.foreign_currency = Some(ForeignCurrency { code: "USD".to_string(), exchange_rate: 83.0 });
+        let totals = InvoiceTotals { total_inr: Money::from_rupees(83000.0), ..totals };
+        let placeholders = invoice_placeholders(&invoice, &totals);
+        assert_eq!(placeholder(&placeholders, "total"), "USD 1,000.00");
+        assert_eq!(
+            placeholder(&placeholders, "amount_in_words"),
+            "rupee equivalent Rs. 83,000.00, Rupees Eighty Three Thousand Only"
+        );
+    }
+
+    /// A plain SMTP server on localhost that hands back the first message it receives.
+    fn smtp_stand_in() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut out = stream.try_clone().unwrap();
+            let mut lines = BufReader::new(stream);
+            let mut message = String::new();
+            let mut in_data = false;
+            out.write_all(b"220 localhost ready\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if lines.read_line(&mut line).unwrap_or(0) == 0 {
+                    break;
+                }
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        sender.send(std::mem::take(&mut message)).unwrap();
+                        out.write_all(b"250 queued\r\n").unwrap();
+                    } else {
+                        message.push_str(&line);
+                    }
+                } else if line.to_ascii_uppercase().starts_with("DATA") {
+                    in_data = true;
+                    out.write_all(b"354 go ahead\r\n").unwrap();
+                } else if line.to_ascii_uppercase().starts_with("QUIT") {
+                    out.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    out.write_all(b"250 ok\r\n").unwrap();
+                }
+            }
+        });
+        (port, receiver)
+    }
+
+    #[test]
+    fn send_email_delivers_to_a_local_server() {
+        let (port, received) = smtp_stand_in();
+        let settings = EmailSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from_name: "Me".to_string(),
+            from_email: "billing@me.in".to_string(),
+            subject_template: None,
+            body_template: None,
+        };
+        let attachment = ("INV-7.pdf".to_string(), b"%PDF-1.4".to_vec());
+
+        send_email(&settings, &["accounts@pune.in".to_string()], "Invoice INV-7", "Please find attached.", Some(attachment)).unwrap();
+
+        let message = received.recv().unwrap();
+        assert!(message.contains("To: accounts@pune.in"));
+        assert!(message.contains("Subject: Invoice INV-7"));
+        assert!(message.contains("Please find attached."));
+        assert!(message.contains("application/pdf") && message.contains("INV-7.pdf"));
+    }
+}
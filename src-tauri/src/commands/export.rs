@@ -0,0 +1,479 @@
+use std::path::Path;
+
+use rusqlite::types::Value;
+use serde::Deserialize;
+use tauri::command;
+
+use crate::{
+    commands::{invoice::fetch_invoices, user::load_rounding_rules},
+    db::get_connection,
+    models::{
+        invoice::{DocumentType, Invoice, InvoiceStatus, RoundingRules, SupplyType, TaxSplit},
+        money::{Money, Percent},
+    },
+    utils::{
+        amount::amount_in_words,
+        table::{write_tables, Cell, ExportFormat, Table},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ExportFilter {
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    pub recipient_name: Option<String>,
+    pub recipient_gst_number: Option<String>,
+    pub document_type: Option<DocumentType>,
+    pub supply_type: Option<SupplyType>,
+    pub status: Option<InvoiceStatus>, // Cancelled invoices are left out unless asked for
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub include_items: bool,
+    pub path: String,
+}
+
+const INVOICE_HEADERS: [&str; 43] = [
+    "Invoice No",
+    "Invoice Date",
+    "Document Type",
+    "Status",
+    "Recipient Name",
+    "Recipient GSTIN",
+    "Recipient City",
+    "Recipient State",
+    "Recipient Pincode",
+    "Ship To Name",
+    "Ship To GSTIN",
+    "Ship To State",
+    "Ship To Pincode",
+    "Place of Supply",
+    "Supply Type",
+    "LUT/Bond Reference",
+    "Shipping Bill No",
+    "Shipping Bill Date",
+    "Port Code",
+    "Reverse Charge",
+    "Discount",
+    "Taxable Value",
+    "CGST %",
+    "CGST Amount",
+    "SGST %",
+    "SGST Amount",
+    "IGST %",
+    "IGST Amount",
+    "Cess Amount",
+    "Total Tax",
+    "Additional Charges",
+    "Non-taxable Charges",
+    "TCS %",
+    "TCS Amount",
+    "Round Off",
+    "Invoice Total",
+    "TDS Expected",
+    "Currency",
+    "Exchange Rate",
+    "Taxable Value (INR)",
+    "Total Tax (INR)",
+    "Invoice Total (INR)",
+    "Amount in Words",
+];
+
+const ITEM_HEADERS: [&str; 19] = [
+    "Invoice No",
+    "Invoice Date",
+    "Recipient Name",
+    "Recipient GSTIN",
+    "Description",
+    "HSN/SAC",
+    "Quantity",
+    "Unit",
+    "Rate",
+    "Discount",
+    "Taxable Value",
+    "CGST %",
+    "CGST Amount",
+    "SGST %",
+    "SGST Amount",
+    "IGST %",
+    "IGST Amount",
+    "Cess Amount",
+    "Line Total",
+];
+
+/// Exports the filtered invoices and returns the paths written.
+///
+/// XLSX gets an "Invoices" sheet and, with `include_items`, an "Items" sheet. CSV writes
+/// the items next to the invoice file as `<name>_items.csv`.
+#[command]
+pub fn export_invoices(filter: ExportFilter, options: ExportOptions) -> Result<Vec<String>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT * FROM invoice WHERE 1=1".to_string();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(from_date) = filter.from_date {
+        values.push(from_date.into());
+        query.push_str(&format!(" AND invoice_date >= ?{}", values.len()));
+    }
+    if let Some(to_date) = filter.to_date {
+        values.push(to_date.into());
+        query.push_str(&format!(" AND invoice_date <= ?{}", values.len()));
+    }
+    if let Some(name) = filter.recipient_name {
+        values.push(format!("%{}%", name).into());
+        query.push_str(&format!(" AND recipient_name LIKE ?{}", values.len()));
+    }
+    if let Some(gst_number) = filter.recipient_gst_number {
+        values.push(gst_number.into());
+        query.push_str(&format!(" AND recipient_gst_number = ?{}", values.len()));
+    }
+    if let Some(document_type) = filter.document_type {
+        values.push(document_type.as_str().to_string().into());
+        query.push_str(&format!(" AND document_type = ?{}", values.len()));
+    }
+    if let Some(supply_type) = filter.supply_type {
+        values.push(supply_type.as_str().to_string().into());
+        query.push_str(&format!(" AND supply_type = ?{}", values.len()));
+    }
+    match filter.status {
+        Some(status) => {
+            values.push(status.as_str().to_string().into());
+            query.push_str(&format!(" AND status = ?{}", values.len()));
+        }
+        None => query.push_str(" AND status != 'cancelled'"),
+    }
+    query.push_str(" ORDER BY invoice_date, invoice_number");
+
+    let invoices = fetch_invoices(&conn, &query, &values)?;
+    let rules = load_rounding_rules(&conn)?;
+
+    let mut tables = vec![invoice_table(&invoices, rules)];
+    if options.include_items {
+        tables.push(item_table(&invoices));
+    }
+
+    write_tables("Invoices", &[], &tables, options.format, Path::new(&options.path))
+}
+
+/// `rules` are the profile's, for invoices saved before they kept their own.
+fn invoice_table(invoices: &[Invoice], rules: RoundingRules) -> Table {
+    let mut table = Table::new("Invoices", &INVOICE_HEADERS);
+
+    for invoice in invoices {
+        let tax = invoice.tax_summary(invoice.rounding_rules.unwrap_or(rules).tax_rounding);
+        // Rates only show up in the columns for the taxes that actually apply
+        let rates = LineTax::at_invoice_rates(invoice.tax_split(), Money::ZERO);
+        let shipping_bill = invoice.shipping_bill.as_ref();
+        let currency = invoice.foreign_currency.as_ref();
+        let recipient_address = invoice.recipient_address_details.as_ref();
+        let ship_to = invoice.ship_to.as_ref();
+
+        table.push(vec![
+            invoice.invoice_number.as_str().into(),
+            Cell::Date(invoice.invoice_date.clone()),
+            invoice.document_type.label().into(),
+            invoice.status.label().into(),
+            invoice.recipient_name.as_str().into(),
+            invoice.recipient_gst_number.as_str().into(),
+            recipient_address.map_or("", |address| address.city.as_str()).into(),
+            recipient_address.and_then(|address| address.state()).map_or("", |state| state.name).into(),
+            recipient_address.map_or("", |address| address.pincode.as_str()).into(),
+            ship_to.map_or("", |ship_to| ship_to.name.as_str()).into(),
+            ship_to.and_then(|ship_to| ship_to.gst_number.as_deref()).unwrap_or_default().into(),
+            ship_to.and_then(|ship_to| ship_to.address.state()).map_or("", |state| state.name).into(),
+            ship_to.map_or("", |ship_to| ship_to.address.pincode.as_str()).into(),
+            // GSTR-1 style, e.g. 27-Maharashtra
+            invoice
+                .place_of_supply_state()
+                .map_or(String::new(), |state| format!("{}-{}", state.code, state.name))
+                .into(),
+            invoice.supply_type.label().into(),
+            invoice.lut_reference.as_deref().unwrap_or_default().into(),
+            shipping_bill.map_or("", |bill| bill.number.as_str()).into(),
+            shipping_bill.map_or(Cell::Text(String::new()), |bill| Cell::Date(bill.date.clone())),
+            shipping_bill.map_or("", |bill| bill.port_code.as_str()).into(),
+            if invoice.reverse_charge { "Y" } else { "N" }.into(),
+            invoice.discount_total().into(),
+            tax.taxable_value.into(),
+            rates.cgst_rate.into(),
+            tax.cgst.into(),
+            rates.sgst_rate.into(),
+            tax.sgst.into(),
+            rates.igst_rate.into(),
+            tax.igst.into(),
+            tax.cess.into(),
+            tax.total_tax.into(),
+            invoice.charges_total().into(),
+            invoice.non_taxable_charges().into(),
+            invoice.tcs_percentage.unwrap_or_default().into(),
+            invoice.tcs_amount.into(),
+            invoice.round_off.into(),
+            invoice.total.into(),
+            invoice.tds_expected.into(),
+            currency.map_or("INR", |currency| currency.code.as_str()).into(),
+            currency.map_or(1.0, |currency| currency.exchange_rate).into(),
+            invoice.to_inr(tax.taxable_value).into(),
+            invoice.to_inr(tax.total_tax).into(),
+            invoice.total_inr.into(),
+            amount_in_words(invoice.total_inr).into(),
+        ]);
+    }
+
+    table
+}
+
+fn item_table(invoices: &[Invoice]) -> Table {
+    let mut table = Table::new("Items", &ITEM_HEADERS);
+
+    for invoice in invoices {
+        let split = invoice.tax_split();
+        let invoice_cells = || -> Vec<Cell> {
+            vec![
+                invoice.invoice_number.as_str().into(),
+                Cell::Date(invoice.invoice_date.clone()),
+                invoice.recipient_name.as_str().into(),
+                invoice.recipient_gst_number.as_str().into(),
+            ]
+        };
+
+        for item in &invoice.items {
+            let taxable = item.net_amount();
+            let tax = LineTax {
+                cess: if split == TaxSplit::NoTax { Money::ZERO } else { item.cess_amount() },
+                ..LineTax::at_invoice_rates(split, taxable)
+            };
+
+            let mut row = invoice_cells();
+            row.extend([
+                item.description.as_str().into(),
+                item.hsn_code.as_str().into(),
+                item.quantity.into(),
+                item.unit.as_str().into(),
+                item.rate.into(),
+                item.discount_amount().into(),
+                taxable.into(),
+            ]);
+            row.extend(tax.cells(taxable));
+            table.push(row);
+        }
+
+        // The invoice discount is a negative line, as the tax engine counts it, so the
+        // item rows add up to the invoice's taxable value
+        let invoice_discount = invoice.invoice_discount();
+        if !invoice_discount.is_zero() {
+            let taxable = -invoice_discount;
+            let tax = LineTax::at_invoice_rates(split, taxable);
+
+            let mut row = invoice_cells();
+            row.extend([
+                "Invoice discount".into(),
+                "".into(),
+                "".into(),
+                "".into(),
+                "".into(),
+                invoice_discount.into(),
+                taxable.into(),
+            ]);
+            row.extend(tax.cells(taxable));
+            table.push(row);
+        }
+
+        // Charges get lines of their own so HSN/SAC-wise figures add up to the invoice
+        for charge in invoice.additional_charges.iter().flatten() {
+            let tax = match (charge.taxable, charge.gst_rate) {
+                (false, _) => LineTax::default(),
+                (true, None) => LineTax::at_invoice_rates(split, charge.amount),
+                (true, Some(rate)) => LineTax::at_own_rate(split, rate, charge.amount),
+            };
+            let taxable = if charge.taxable { charge.amount } else { Money::ZERO };
+
+            let mut row = invoice_cells();
+            row.extend([
+                charge.description.as_str().into(),
+                charge.hsn_code.as_deref().unwrap_or_default().into(),
+                "".into(),
+                "".into(),
+                charge.amount.into(),
+                Money::ZERO.into(),
+                taxable.into(),
+            ]);
+            row.extend(tax.cells(charge.amount));
+            table.push(row);
+        }
+    }
+
+    table
+}
+
+/// Rates and tax on one exported line, zero for the taxes that don't apply.
+#[derive(Default)]
+struct LineTax {
+    cgst_rate: f64,
+    cgst: Money,
+    sgst_rate: f64,
+    sgst: Money,
+    igst_rate: f64,
+    igst: Money,
+    cess: Money,
+}
+
+impl LineTax {
+    /// A line at the invoice's own rates, split the way `Invoice::tax_summary` splits them.
+    fn at_invoice_rates(split: TaxSplit, taxable: Money) -> Self {
+        match split {
+            TaxSplit::NoTax => LineTax::default(),
+            TaxSplit::Igst(rate) => LineTax { igst_rate: rate.to_f64(), igst: taxable.percent(rate), ..Default::default() },
+            TaxSplit::CgstSgst(cgst, sgst) => LineTax {
+                cgst_rate: cgst.to_f64(),
+                cgst: taxable.percent(cgst),
+                sgst_rate: sgst.to_f64(),
+                sgst: taxable.percent(sgst),
+                ..Default::default()
+            },
+            TaxSplit::HalvedIgst(rate) => LineTax::halved(rate, taxable),
+        }
+    }
+
+    /// A line with a GST rate of its own, split evenly between CGST and SGST within a state.
+    fn at_own_rate(split: TaxSplit, rate: Percent, taxable: Money) -> Self {
+        match split {
+            TaxSplit::NoTax => LineTax::default(),
+            TaxSplit::Igst(_) => LineTax { igst_rate: rate.to_f64(), igst: taxable.percent(rate), ..Default::default() },
+            TaxSplit::CgstSgst(..) | TaxSplit::HalvedIgst(_) => LineTax::halved(rate, taxable),
+        }
+    }
+
+    fn halved(rate: Percent, taxable: Money) -> Self {
+        let half = taxable.percent_halved(rate);
+        LineTax {
+            cgst_rate: rate.to_f64() / 2.0,
+            cgst: half,
+            sgst_rate: rate.to_f64() / 2.0,
+            sgst: half,
+            ..Default::default()
+        }
+    }
+
+    fn total(&self) -> Money {
+        self.cgst + self.sgst + self.igst + self.cess
+    }
+
+    /// The rate and amount columns followed by the line total, `value` plus the tax.
+    fn cells(&self, value: Money) -> Vec<Cell> {
+        vec![
+            self.cgst_rate.into(),
+            self.cgst.into(),
+            self.sgst_rate.into(),
+            self.sgst.into(),
+            self.igst_rate.into(),
+            self.igst.into(),
+            self.cess.into(),
+            (value + self.total()).into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::invoice::TaxRounding;
+    use serde_json::json;
+
+    fn test_invoice(details: serde_json::Value) -> Invoice {
+        let mut invoice = json!({
+            "issuer_name": "Seller", "issuer_address": "Pune", "issuer_gst_number": "27AAAAA0000A1Z5", "issuer_phone": "1",
+            "recipient_name": "Buyer", "recipient_address": "Mumbai", "recipient_gst_number": "27BBBBB0000B1Z5", "recipient_phone": "2",
+            "invoice_number": "T-1", "invoice_date": "2024-10-01", "amount": 0.0, "total": 0.0,
+            "bank_details": {"bank_name": "B", "branch": "Br", "account_name": "Seller", "account_no": "123", "ifsc_code": "HDFC0001234"},
+            "transport_details": {"transporter_name": "SELF", "place_of_supply": "27"},
+            "items": [], "reverse_charge": false
+        });
+        invoice.as_object_mut().unwrap().extend(details.as_object().unwrap().clone());
+        serde_json::from_value(invoice).unwrap()
+    }
+
+    /// Sum of a numeric column over the rows of `table`.
+    fn column_total(table: &Table, header: &str) -> Money {
+        let index = table.headers.iter().position(|name| name == header).unwrap();
+        table
+            .rows
+            .iter()
+            .map(|row| match &row[index] {
+                Cell::Number(number) => Money::from_rupees(*number),
+                _ => Money::ZERO,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn item_rows_add_up_to_the_invoice() {
+        let invoice = test_invoice(json!({
+            "cgst_percentage": 9.0, "sgst_percentage": 9.0,
+            "items": [
+                {"description": "A", "hsn_code": "8471", "quantity": 3.0, "unit": "NOS", "rate": 33.33, "amount": 99.99,
+                    "discount": {"kind": "percent", "value": 10.0}},
+                {"description": "B", "hsn_code": "8471", "quantity": 1.0, "unit": "NOS", "rate": 50.0, "amount": 50.0}
+            ],
+            "discount": {"kind": "percent", "value": 7.5},
+            "additional_charges": [
+                {"description": "Freight", "amount": 40.0, "hsn_code": "996511", "gst_rate": 12.0},
+                {"description": "Insurance", "amount": 5.0, "taxable": false}
+            ]
+        }));
+        let table = item_table(std::slice::from_ref(&invoice));
+        let tax = invoice.tax_summary(TaxRounding::PerLine);
+
+        assert_eq!(column_total(&table, "Taxable Value"), invoice.taxable_value());
+        assert_eq!(column_total(&table, "CGST Amount"), tax.cgst);
+        assert_eq!(column_total(&table, "SGST Amount"), tax.sgst);
+        assert_eq!(column_total(&table, "IGST Amount"), tax.igst);
+    }
+
+    #[test]
+    fn item_taxes_follow_the_invoice_split() {
+        let items = json!([{"description": "A", "hsn_code": "8471", "quantity": 1.0, "unit": "NOS", "rate": 100.05, "amount": 100.05,
+            "cess": {"ad_valorem": 1.0}}]);
+        // (invoice details, CGST, SGST, IGST, cess)
+        let cases = [
+            (json!({"cgst_percentage": 9.0, "sgst_percentage": 9.0}), 9.0, 9.0, 0.0, 1.0),
+            // Only IGST filled in on an intra-state invoice
+            (json!({"igst_percentage": 18.0}), 9.0, 9.0, 0.0, 1.0),
+            (json!({"cgst_percentage": 9.0, "sgst_percentage": 9.0, "place_of_supply": "29"}), 0.0, 0.0, 18.0, 1.0),
+            (json!({"igst_percentage": 18.0, "supply_type": "export_without_payment", "lut_reference": "AD270324000001"}), 0.0, 0.0, 0.0, 0.0),
+        ];
+        for (details, cgst, sgst, igst, cess) in cases {
+            let mut details = details;
+            details["items"] = items.clone();
+            let invoice = test_invoice(details);
+            let table = item_table(std::slice::from_ref(&invoice));
+            let tax = invoice.tax_summary(TaxRounding::PerLine);
+            let taxable = Money::from_rupees(100.05);
+
+            assert_eq!(column_total(&table, "CGST Amount"), taxable.percent(Percent::from_f64(cgst)), "{:?}", invoice.tax_split());
+            assert_eq!(column_total(&table, "SGST Amount"), taxable.percent(Percent::from_f64(sgst)), "{:?}", invoice.tax_split());
+            assert_eq!(column_total(&table, "IGST Amount"), taxable.percent(Percent::from_f64(igst)), "{:?}", invoice.tax_split());
+            assert_eq!(column_total(&table, "Cess Amount"), taxable.percent(Percent::from_f64(cess)), "{:?}", invoice.tax_split());
+            assert_eq!(column_total(&table, "CGST Amount") + column_total(&table, "SGST Amount"), tax.cgst + tax.sgst);
+            assert_eq!(column_total(&table, "IGST Amount"), tax.igst);
+        }
+    }
+
+    #[test]
+    fn invoice_rows_give_gst_figures_in_rupees() {
+        let invoice = test_invoice(json!({
+            "igst_percentage": 18.0, "supply_type": "export_with_payment",
+            "foreign_currency": {"code": "USD", "exchange_rate": 83.5},
+            "items": [{"description": "A", "hsn_code": "998314", "quantity": 1.0, "unit": "NOS", "rate": 100.0, "amount": 100.0}]
+        }));
+        let rules = RoundingRules { tax_rounding: TaxRounding::PerInvoice, round_off: Default::default() };
+        let table = invoice_table(std::slice::from_ref(&invoice), rules);
+
+        assert_eq!(column_total(&table, "Taxable Value"), Money::from_rupees(100.0));
+        assert_eq!(column_total(&table, "Taxable Value (INR)"), Money::from_rupees(8350.0));
+        assert_eq!(column_total(&table, "Total Tax (INR)"), Money::from_rupees(1503.0));
+        assert_eq!(column_total(&table, "IGST %"), Money::from_rupees(18.0));
+    }
+}
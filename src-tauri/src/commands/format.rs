@@ -0,0 +1,18 @@
+use tauri::command;
+
+use crate::{
+    models::money::Money,
+    utils::amount::{amount_in_words, format_indian},
+};
+
+/// Amount in words for the print view, e.g. "Rupees One Lakh and Fifty Paise Only".
+#[command]
+pub fn amount_to_words(amount: Money) -> String {
+    amount_in_words(amount)
+}
+
+/// Amount with two decimals grouped in lakhs and crores, e.g. "1,00,000.50".
+#[command]
+pub fn format_indian_amount(amount: f64) -> String {
+    format_indian(amount)
+}
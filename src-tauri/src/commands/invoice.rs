@@ -1,8 +1,9 @@
-use rusqlite::{params, OpenFlags};
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, params_from_iter, types::Value, Connection, OpenFlags, OptionalExtension, Row};
 use serde::Deserialize;
 use tauri::{command, Error};
 use uuid::Uuid;
-use crate::{db::get_connection, models::invoice::Invoice, utils::get_app_data_path};
+use crate::{commands::{bank::{default_bank_account, load_bank_account}, company::{default_address_book_entry, load_address_book_entry, load_company_address}, payment::{invoice_balance, refresh_invoice_status}, product::validate_items_against_catalog, stock::{record_invoice_stock, reverse_invoice_stock}, user::{load_profile, load_profile_address, load_rounding_rules}}, db::get_connection, models::{bank::validate_bank_details, challan::ChallanStatus, invoice::{AdditionalCharges, Discount, DocumentType, ForeignCurrency, Invoice, InvoiceItem, InvoiceStatus, InvoiceTotals, RoundOff, RoundingRules, ShipTo, TaxRounding}, money::{Money, Percent}, state::{find_state, OTHER_COUNTRIES}}};
 
 #[derive(Deserialize, Debug)]
 pub struct InvoiceFilter {
@@ -14,13 +15,141 @@ pub struct InvoiceFilter {
 
 #[command]
 pub fn create_invoice(invoice: Invoice) -> Result<String, String> {
-    let conn = get_connection().map_err(|e| e.to_string())?;
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    insert_invoice(&tx, &invoice)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "{} {} created successfully!",
+        invoice.document_type.label(),
+        invoice.invoice_number
+    ))
+}
+
+/// Cancels an invoice and puts any stock it issued back. Invoices with payments or
+/// credit notes settled against them have to have those removed first.
+#[command]
+pub fn cancel_invoice(id: String) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let (invoice_number, status, document_type, original_invoice_id): (String, InvoiceStatus, DocumentType, Option<String>) = tx
+        .query_row(
+            "SELECT invoice_number, status, document_type, original_invoice_id FROM invoice WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invoice {} not found", id))?;
+
+    if status == InvoiceStatus::Cancelled {
+        return Err(format!("Invoice {} is already cancelled", invoice_number));
+    }
+    let payments: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM payment_allocation WHERE invoice_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if payments > 0 {
+        return Err(format!("Invoice {} has payments recorded against it", invoice_number));
+    }
+    // The goods a credit note took back would otherwise be returned to stock twice
+    let credit_notes: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM invoice WHERE original_invoice_id = ?1 AND document_type = ?2 AND status != ?3",
+            params![id, DocumentType::CreditNote, InvoiceStatus::Cancelled],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if credit_notes > 0 {
+        return Err(format!("Invoice {} has credit notes raised against it, cancel those first", invoice_number));
+    }
+
+    tx.execute(
+        "UPDATE invoice SET status = ?1 WHERE id = ?2",
+        params![InvoiceStatus::Cancelled, id],
+    )
+    .map_err(|e| e.to_string())?;
+    reverse_invoice_stock(&tx, &id)?;
+
+    // Challans billed on the invoice go back to open so they can be invoiced again
+    tx.execute(
+        "UPDATE delivery_challan SET status = ?1, invoice_id = NULL WHERE invoice_id = ?2",
+        params![ChallanStatus::Open, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // A cancelled credit note no longer reduces the invoice it was raised against
+    if let (DocumentType::CreditNote, Some(original_invoice_id)) = (document_type, original_invoice_id) {
+        refresh_invoice_status(&tx, &original_invoice_id)?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(format!("Invoice {} cancelled.", invoice_number))
+}
+
+/// Stores a new invoice or credit/debit note and applies its side effects (client
+/// link, settlement of the original invoice, stock). Returns the new invoice id.
+pub(crate) fn insert_invoice(conn: &Connection, invoice: &Invoice) -> Result<String, String> {
+    validate_items_against_catalog(conn, &invoice.items)?;
+    validate_discounts_and_charges(&invoice.items, invoice.discount, &invoice.additional_charges)?;
+    validate_supply(invoice)?;
+    let mut invoice = Invoice {
+        rounding_rules: Some(load_rounding_rules(conn)?),
+        ..invoice.clone()
+    };
+    for (name, rate) in [("TCS", invoice.tcs_percentage), ("TDS", invoice.tds_percentage)] {
+        if rate.is_some_and(|rate| rate < Percent::ZERO || rate > Percent::HUNDRED) {
+            return Err(format!("{} rate must be between 0% and 100%", name));
+        }
+    }
+
+    // The document dialog only sends the recipient details, so fall back to the client with that GSTIN
+    let company_id = match &invoice.company_id {
+        Some(id) => Some(id.clone()),
+        None => conn
+            .query_row(
+                "SELECT id FROM company WHERE gst_number = ?1 LIMIT 1",
+                params![invoice.recipient_gst_number],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?,
+    };
+
+    // The ship-to state decides the place of supply when none is given, so addresses come first
+    snapshot_addresses(conn, &mut invoice, company_id.as_deref())?;
+    invoice.place_of_supply = Some(resolve_place_of_supply(&invoice)?);
+
+    // The total is always worked out here so the round-off follows the profile's rules,
+    // which are kept with the invoice so later changes to them don't alter it
+    let totals = invoice.totals(&invoice_rounding_rules(conn, &invoice)?);
+    validate_original_invoice(conn, &invoice, company_id.as_deref(), totals.total_inr)?;
+
     let items_json = serde_json::to_string(&invoice.items).map_err(|e| e.to_string())?;
-    let bank_details_json = serde_json::to_string(&invoice.bank_details).map_err(|e| e.to_string())?;
     let transport_details_json = serde_json::to_string(&invoice.transport_details).map_err(|e| e.to_string())?;
     let additional_charges_json = serde_json::to_string(&invoice.additional_charges)
     .map_err(|e| e.to_string())?;
-    
+    let discount_json = serde_json::to_string(&invoice.discount).map_err(|e| e.to_string())?;
+    let shipping_bill_json = serde_json::to_string(&invoice.shipping_bill).map_err(|e| e.to_string())?;
+    resolve_bank_details(conn, &mut invoice)?;
+    let issuer_address_json = serde_json::to_string(&invoice.issuer_address_details).map_err(|e| e.to_string())?;
+    let recipient_address_json = serde_json::to_string(&invoice.recipient_address_details).map_err(|e| e.to_string())?;
+    let ship_to_json = serde_json::to_string(&invoice.ship_to).map_err(|e| e.to_string())?;
+    let bank_details_json = serde_json::to_string(&invoice.bank_details).map_err(|e| e.to_string())?;
+    let (currency, exchange_rate) = match &invoice.foreign_currency {
+        Some(currency) => (currency.code.as_str(), currency.exchange_rate),
+        None => ("INR", 1.0),
+    };
+
+    let id = Uuid::new_v4().to_string();
     conn.execute(
         "INSERT INTO invoice (
         id,
@@ -45,10 +174,35 @@ pub fn create_invoice(invoice: Invoice) -> Result<String, String> {
         total_amount,
         reverse_charge,
         bank_details_json,
-        transport_details_json
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+        transport_details_json,
+        company_id,
+        status,
+        document_type,
+        original_invoice_id,
+        round_off,
+        discount_json,
+        tcs_percentage,
+        tcs_amount,
+        tds_percentage,
+        tds_expected,
+        supply_type,
+        lut_reference,
+        shipping_bill_json,
+        currency,
+        exchange_rate,
+        total_inr,
+        place_of_supply,
+        issuer_address_json,
+        recipient_address_json,
+        bill_to_address_id,
+        ship_to_json,
+        ship_to_address_id,
+        bank_account_id,
+        tax_rounding,
+        round_off_rule
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39, ?40, ?41, ?42, ?43, ?44, ?45, ?46, ?47, ?48)",
         params![
-            Uuid::new_v4().to_string(), // Generate a new UUID for the invoice ID
+            id,
             invoice.issuer_name,
             invoice.issuer_address,
             invoice.issuer_gst_number,
@@ -62,23 +216,368 @@ pub fn create_invoice(invoice: Invoice) -> Result<String, String> {
             items_json, // This is the serialized JSON string of items
             invoice.invoice_date,
             invoice.invoice_number,
-            invoice.amount,
+            invoice.items_value(),
             invoice.cgst_percentage,
             invoice.sgst_percentage,
             invoice.igst_percentage,
             additional_charges_json,
-            invoice.total,
+            totals.total,
             invoice.reverse_charge,
             bank_details_json,
-            transport_details_json
+            transport_details_json,
+            company_id,
+            InvoiceStatus::Unpaid,
+            invoice.document_type,
+            invoice.original_invoice_id,
+            totals.round_off,
+            discount_json,
+            invoice.tcs_percentage,
+            totals.tcs,
+            invoice.tds_percentage,
+            totals.tds_expected,
+            invoice.supply_type,
+            invoice.lut_reference,
+            shipping_bill_json,
+            currency,
+            exchange_rate,
+            totals.total_inr,
+            invoice.place_of_supply,
+            issuer_address_json,
+            recipient_address_json,
+            invoice.bill_to_address_id,
+            ship_to_json,
+            invoice.ship_to_address_id,
+            invoice.bank_account_id,
+            invoice.rounding_rules.map(|rules| rules.tax_rounding),
+            invoice.rounding_rules.map(|rules| rules.round_off)
         ],
     )
     .map_err(|e| e.to_string())?;
 
-    Ok(format!(
-        "Invoice {} created successfully!",
-        invoice.invoice_number
-    ))
+    // A credit note reduces what is still owed on the invoice it was raised against
+    if invoice.document_type == DocumentType::CreditNote {
+        if let Some(original_invoice_id) = &invoice.original_invoice_id {
+            refresh_invoice_status(conn, original_invoice_id)?;
+        }
+    }
+
+    record_invoice_stock(conn, &id, &invoice)?;
+
+    Ok(id)
+}
+
+/// A credit or debit note has to be raised against an active tax invoice of the same
+/// client, and credit notes cannot take off more than the invoice was for. One larger
+/// than what is still unpaid, e.g. a return after payment, leaves the client in credit.
+fn validate_original_invoice(conn: &Connection, invoice: &Invoice, company_id: Option<&str>, total_inr: Money) -> Result<(), String> {
+    let original_invoice_id = match (invoice.document_type, &invoice.original_invoice_id) {
+        (DocumentType::CreditNote | DocumentType::DebitNote, Some(id)) => id,
+        _ => return Ok(()),
+    };
+    let (document_type, original_company_id, recipient_gst_number): (DocumentType, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT document_type, company_id, recipient_gst_number FROM invoice WHERE id = ?1",
+            params![original_invoice_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invoice {} not found", original_invoice_id))?;
+    let balance = invoice_balance(conn, original_invoice_id)?;
+
+    if document_type != DocumentType::TaxInvoice {
+        return Err(format!("{} is a {}, notes can only be raised against a tax invoice", balance.invoice_number, document_type.label()));
+    }
+    if balance.status == InvoiceStatus::Cancelled {
+        return Err(format!("Invoice {} is cancelled", balance.invoice_number));
+    }
+    let same_client = match (company_id, original_company_id.as_deref()) {
+        (Some(company_id), Some(original_company_id)) => company_id == original_company_id,
+        _ => recipient_gst_number.as_deref() == Some(invoice.recipient_gst_number.as_str()),
+    };
+    if !same_client {
+        return Err(format!("Invoice {} was issued to a different client", balance.invoice_number));
+    }
+    if invoice.document_type == DocumentType::CreditNote && total_inr > balance.total - balance.credited {
+        return Err(format!(
+            "Invoice {} is for {} and {} has already been credited, a credit note of {} is too large",
+            balance.invoice_number, balance.total, balance.credited, total_inr
+        ));
+    }
+    Ok(())
+}
+
+/// Copies the picked bank account onto the invoice, or the default one when no account
+/// was picked and no details were typed in. Typed in details are checked instead. A pick
+/// whose account has since been deleted counts as no pick.
+fn resolve_bank_details(conn: &Connection, invoice: &mut Invoice) -> Result<(), String> {
+    let picked = match &invoice.bank_account_id {
+        Some(id) => load_bank_account(conn, id)?,
+        None => None,
+    };
+    let account = match picked {
+        Some(account) => Some(account),
+        None if invoice.bank_details.account_no.trim().is_empty() => default_bank_account(conn)?,
+        None => {
+            validate_bank_details(&invoice.bank_details)?;
+            None
+        }
+    };
+    match account {
+        Some(account) => {
+            invoice.bank_details = account.details();
+            invoice.bank_account_id = account.id;
+        }
+        None => invoice.bank_account_id = None,
+    }
+    Ok(())
+}
+
+/// Copies the addresses onto the invoice so later edits to the profile, client or address
+/// book leave it as issued. Bill-to and ship-to come from the picked address book entries,
+/// then from what was typed in, then from the client's defaults.
+fn snapshot_addresses(conn: &Connection, invoice: &mut Invoice, company_id: Option<&str>) -> Result<(), String> {
+    invoice.issuer_address_details = match &invoice.issuer_address_details {
+        Some(address) => Some(address.normalized()),
+        None => load_profile_address(conn)?,
+    };
+
+    let bill_to = match (&invoice.bill_to_address_id, company_id) {
+        (Some(id), _) => Some(load_address_book_entry(conn, id, company_id)?),
+        (None, Some(company_id)) if invoice.recipient_address_details.is_none() => {
+            default_address_book_entry(conn, company_id, false)?
+        }
+        _ => None,
+    };
+    match (bill_to, &invoice.recipient_address_details, company_id) {
+        (Some(entry), _, _) => {
+            // A branch registered on its own is billed under its own GSTIN
+            if let Some(gst_number) = entry.gst_number.filter(|gst_number| !gst_number.trim().is_empty()) {
+                invoice.recipient_gst_number = gst_number;
+            }
+            invoice.recipient_address = entry.address.to_string();
+            invoice.recipient_address_details = Some(entry.address);
+            invoice.bill_to_address_id = entry.id;
+        }
+        (None, Some(address), _) => {
+            let address = address.normalized();
+            address.validate().map_err(|e| format!("Bill-to address: {}", e))?;
+            invoice.recipient_address_details = Some(address);
+        }
+        (None, None, Some(company_id)) => invoice.recipient_address_details = load_company_address(conn, company_id)?,
+        (None, None, None) => {}
+    }
+
+    let ship_to = match (&invoice.ship_to_address_id, company_id) {
+        (Some(id), _) => Some(load_address_book_entry(conn, id, company_id)?),
+        (None, Some(company_id)) if invoice.ship_to.is_none() => default_address_book_entry(conn, company_id, true)?,
+        _ => None,
+    };
+    match (ship_to, &invoice.ship_to) {
+        (Some(entry), _) => {
+            invoice.ship_to = Some(ShipTo {
+                name: invoice.recipient_name.clone(),
+                gst_number: entry.gst_number.or_else(|| Some(invoice.recipient_gst_number.clone())),
+                address: entry.address,
+            });
+            invoice.ship_to_address_id = entry.id;
+        }
+        (None, Some(ship_to)) => {
+            if ship_to.name.trim().is_empty() {
+                return Err("Ship-to party needs a name".to_string());
+            }
+            let address = ship_to.address.normalized();
+            address.validate().map_err(|e| format!("Ship-to address: {}", e))?;
+            invoice.ship_to = Some(ShipTo { address, ..ship_to.clone() });
+        }
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+/// Hands out the next number in the profile's invoice series (`PREFIX-n`) and moves the
+/// counter on, skipping numbers that were already used by hand.
+pub(crate) fn allocate_invoice_number(conn: &Connection) -> Result<String, String> {
+    let (invoice_number, number) = next_free_invoice_number(conn)?;
+
+    conn.execute(
+        "UPDATE profile SET next_invoice_number = ?1",
+        params![number + 1],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(invoice_number)
+}
+
+/// The next unused number in the invoice series, without taking it.
+fn next_free_invoice_number(conn: &Connection) -> Result<(String, u32), String> {
+    let profile = load_profile(conn)?;
+
+    let mut number = profile.next_invoice_number;
+    loop {
+        let candidate = format!("{}-{}", profile.invoice_prefix, number);
+        let taken: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM invoice WHERE invoice_number = ?1)",
+                params![candidate],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !taken {
+            return Ok((candidate, number));
+        }
+        number += 1;
+    }
+}
+
+/// An unsaved tax invoice copied from `source`, dated today and carrying the next free
+/// number. The number is only taken once the draft is saved.
+pub(crate) fn new_draft(conn: &Connection, source: &Invoice) -> Result<Invoice, String> {
+    let (invoice_number, _) = next_free_invoice_number(conn)?;
+
+    Ok(Invoice {
+        id: None,
+        invoice_number,
+        invoice_date: Local::now().format("%Y-%m-%d").to_string(),
+        status: InvoiceStatus::Unpaid,
+        document_type: DocumentType::TaxInvoice,
+        original_invoice_id: None,
+        rounding_rules: None,
+        bank_account_id: None,
+        ..source.clone()
+    })
+}
+
+/// Copies an earlier invoice's recipient, items, charges and transport details into a new
+/// draft for the document dialog.
+#[command]
+pub fn duplicate_invoice(id: String) -> Result<Invoice, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let source = fetch_invoices(&conn, "SELECT * FROM invoice WHERE id = ?1", &[id.clone().into()])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Invoice {} not found", id))?;
+
+    new_draft(&conn, &source)
+}
+
+/// Supplies under a LUT or bond need its reference, and only exports and SEZ supplies
+/// can be billed in a foreign currency.
+fn validate_supply(invoice: &Invoice) -> Result<(), String> {
+    let supply_type = invoice.supply_type;
+
+    if supply_type.is_without_payment() && invoice.lut_reference.as_deref().is_none_or(|r| r.trim().is_empty()) {
+        return Err(format!("{} needs the LUT or bond reference", supply_type.label()));
+    }
+    if let Some(bill) = &invoice.shipping_bill {
+        if !supply_type.is_export() {
+            return Err("Shipping bill details only apply to exports".to_string());
+        }
+        if bill.number.trim().is_empty() || bill.port_code.trim().is_empty() {
+            return Err("Shipping bill needs its number and port code".to_string());
+        }
+        NaiveDate::parse_from_str(&bill.date, "%Y-%m-%d").map_err(|_| format!("Shipping bill date {} is not valid", bill.date))?;
+    }
+    if let Some(currency) = &invoice.foreign_currency {
+        if !supply_type.is_zero_rated() {
+            return Err("Only exports and SEZ supplies can be billed in a foreign currency".to_string());
+        }
+        let is_code = currency.code.len() == 3 && currency.code.chars().all(|c| c.is_ascii_uppercase());
+        if !is_code || currency.code == "INR" {
+            return Err(format!("{} is not a foreign currency code", currency.code));
+        }
+        if currency.exchange_rate.is_nan() || currency.exchange_rate <= 0.0 {
+            return Err("Exchange rate must be more than zero".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// The GST state code the invoice is supplied to. Taken from the invoice, then from its
+/// transport details, then from the recipient's GSTIN. Exports, and only exports, are
+/// supplied to Other Countries.
+fn resolve_place_of_supply(invoice: &Invoice) -> Result<String, String> {
+    let given = [invoice.place_of_supply.as_deref().unwrap_or_default(), &invoice.transport_details.place_of_supply]
+        .into_iter()
+        .map(str::trim)
+        .find(|value| !value.is_empty());
+    let export = invoice.supply_type.is_export();
+
+    let code = match given {
+        Some(value) => find_state(value).ok_or_else(|| format!("{} is not a known place of supply", value))?.code,
+        None if export => OTHER_COUNTRIES,
+        // Where the goods are delivered, otherwise the recipient's registered state
+        None => invoice
+            .ship_to
+            .as_ref()
+            .and_then(|ship_to| ship_to.address.state())
+            .or_else(|| find_state(&invoice.recipient_gst_number))
+            .ok_or("Place of supply is required")?
+            .code,
+    };
+    if export && code != OTHER_COUNTRIES {
+        return Err(format!("Exports must have Other Countries ({}) as the place of supply", OTHER_COUNTRIES));
+    }
+    if !export && code == OTHER_COUNTRIES {
+        return Err(format!("Only exports can have Other Countries ({}) as the place of supply", OTHER_COUNTRIES));
+    }
+    Ok(code.to_string())
+}
+
+/// Checks discounts, cess and additional charges. A negative charge would
+/// lower the taxable value without showing up as a discount, so those are refused.
+pub(crate) fn validate_discounts_and_charges(
+    items: &[InvoiceItem],
+    discount: Option<Discount>,
+    additional_charges: &Option<Vec<AdditionalCharges>>,
+) -> Result<(), String> {
+    for item in items {
+        if let Some(discount) = item.discount {
+            discount.validate(item.amount).map_err(|e| format!("{}: {}", item.description, e))?;
+        }
+        if item.cess_amount() < Money::ZERO {
+            return Err(format!("{}: cess cannot be negative", item.description));
+        }
+    }
+    if let Some(discount) = discount {
+        let subtotal: Money = items.iter().map(|item| item.net_amount()).sum();
+        discount.validate(subtotal)?;
+    }
+    for charge in additional_charges.iter().flatten() {
+        if charge.amount < Money::ZERO {
+            return Err(format!(
+                "Additional charge {} is negative, enter it as a discount instead",
+                charge.description
+            ));
+        }
+        match charge.gst_rate {
+            Some(_) if !charge.taxable => {
+                return Err(format!("Additional charge {} is not taxable but has a GST rate", charge.description));
+            }
+            Some(rate) if rate < Percent::ZERO || rate > Percent::HUNDRED => {
+                return Err(format!("GST rate of {}% on {} is not valid", rate, charge.description));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Tax, round-off and total for the invoice, under the rounding rules it was issued with
+/// or, for one still being edited, those set in the profile.
+#[command]
+pub fn calculate_invoice_totals(invoice: Invoice) -> Result<InvoiceTotals, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    Ok(invoice.totals(&invoice_rounding_rules(&conn, &invoice)?))
+}
+
+/// The rules an issued invoice was worked out under, the profile's for drafts.
+pub(crate) fn invoice_rounding_rules(conn: &Connection, invoice: &Invoice) -> Result<RoundingRules, String> {
+    match invoice.rounding_rules {
+        Some(rules) => Ok(rules),
+        None => load_rounding_rules(conn),
+    }
 }
 
 #[command]
@@ -86,14 +585,10 @@ pub fn search_invoices(filter: InvoiceFilter) -> Result<Vec<Invoice>, String> {
     let conn = get_connection().map_err(|e| e.to_string())?;
     
     let mut query = String::new();
-    let mut value = rusqlite::types::Value::Null;
+    let mut value = Value::Null;
 
     let mut count = 0;
 
-    println!("Searching invoices with filter: {:?}", &filter);
-    println!("Searching invoices with filter name: {:?}", &filter.recipient_name);
-
-
     if let Some(id) = filter.id {
         query = "SELECT * FROM invoice WHERE id = ?1".into();
         value = id.into();
@@ -122,58 +617,125 @@ pub fn search_invoices(filter: InvoiceFilter) -> Result<Vec<Invoice>, String> {
         return Err("Only one filter can be provided at a time".to_string());
     }
 
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
-    let rows = stmt.query_map(
-        params![value],
-        |row| {
-            let items_json: String = row.get("items_json")?;
-            let bank_details_json: String = row.get("bank_details_json")?;
-            let transport_details_json: String = row.get("transport_details_json")?;
-            let additional_charges_json: String = row.get("additional_charges_json")?;
-            let items = serde_json::from_str(&items_json)
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
-            let bank_details = serde_json::from_str(&bank_details_json)
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
-            let transport_details = serde_json::from_str(&transport_details_json)
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
-            let additional_charges = serde_json::from_str(&additional_charges_json)
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
-
-            Ok(Invoice {
-                id: row.get("id")?,
-                invoice_number: row.get("invoice_number")?,
-                issuer_name: row.get("issuer_name")?,
-                issuer_address: row.get("issuer_address")?,
-                issuer_gst_number: row.get("issuer_gst_number")?,
-                issuer_phone: row.get("issuer_phone")?,
-                issuer_email: row.get("issuer_email")?,
-                recipient_name: row.get("recipient_name")?,
-                recipient_address: row.get("recipient_address")?,
-                recipient_gst_number: row.get("recipient_gst_number")?,
-                recipient_phone: row.get("recipient_phone")?,
-                recipient_email: row.get("recipient_email")?,
-                items: items,
-                bank_details: bank_details,
-                transport_details: transport_details,
-                invoice_date: row.get("invoice_date")?,
-                amount: row.get("amount")?,
-                cgst_percentage: row.get("cgst_percentage")?,
-                sgst_percentage: row.get("sgst_percentage")?,
-                igst_percentage: row.get("igst_percentage")?,
-                additional_charges: additional_charges,
-                total: row.get("total_amount")?,
-                reverse_charge: row.get("reverse_charge")?
-            })
-        },
-    ).map_err(|e| e.to_string())?;
+    fetch_invoices(&conn, &query, &[value])
+}
+
+/// Runs an invoice `SELECT *` query and maps every row into an `Invoice`.
+pub(crate) fn fetch_invoices(conn: &Connection, query: &str, values: &[Value]) -> Result<Vec<Invoice>, String> {
+    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), invoice_from_row)
+        .map_err(|e| e.to_string())?;
 
     let invoices: Result<Vec<Invoice>, rusqlite::Error> = rows.collect();
-    println!("Invoices found: {:?}", invoices);
-    let test_path = get_app_data_path();
-    println!("App data path: {:?}", test_path);
+    invoices.map_err(|e| e.to_string())
+}
 
+pub(crate) fn invoice_from_row(row: &Row) -> rusqlite::Result<Invoice> {
+    let items_json: String = row.get("items_json")?;
+    let bank_details_json: String = row.get("bank_details_json")?;
+    let transport_details_json: String = row.get("transport_details_json")?;
+    let additional_charges_json: Option<String> = row.get("additional_charges_json")?;
+    let discount_json: Option<String> = row.get("discount_json")?;
+    let shipping_bill_json: Option<String> = row.get("shipping_bill_json")?;
+    let currency: String = row.get("currency")?;
+    let issuer_address_json: Option<String> = row.get("issuer_address_json")?;
+    let recipient_address_json: Option<String> = row.get("recipient_address_json")?;
+    let ship_to_json: Option<String> = row.get("ship_to_json")?;
+    let items = serde_json::from_str(&items_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    let bank_details = serde_json::from_str(&bank_details_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    let transport_details = serde_json::from_str(&transport_details_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    let additional_charges = match additional_charges_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let discount = match discount_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let shipping_bill = match shipping_bill_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let issuer_address_details = match issuer_address_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let recipient_address_details = match recipient_address_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let ship_to = match ship_to_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let rounding_rules = match (row.get::<_, Option<TaxRounding>>("tax_rounding")?, row.get::<_, Option<RoundOff>>("round_off_rule")?) {
+        (Some(tax_rounding), Some(round_off)) => Some(RoundingRules { tax_rounding, round_off }),
+        _ => None,
+    };
+    let foreign_currency = if currency == "INR" {
+        None
+    } else {
+        Some(ForeignCurrency { code: currency, exchange_rate: row.get("exchange_rate")? })
+    };
 
-    invoices.map_err(|e| e.to_string())
+    Ok(Invoice {
+        id: row.get("id")?,
+        invoice_number: row.get("invoice_number")?,
+        issuer_name: row.get("issuer_name")?,
+        issuer_address: row.get("issuer_address")?,
+        issuer_gst_number: row.get("issuer_gst_number")?,
+        issuer_phone: row.get("issuer_phone")?,
+        issuer_email: row.get("issuer_email")?,
+        issuer_address_details,
+        recipient_name: row.get("recipient_name")?,
+        recipient_address: row.get("recipient_address")?,
+        recipient_gst_number: row.get("recipient_gst_number")?,
+        recipient_phone: row.get("recipient_phone")?,
+        recipient_email: row.get("recipient_email")?,
+        recipient_address_details,
+        bill_to_address_id: row.get("bill_to_address_id")?,
+        ship_to,
+        ship_to_address_id: row.get("ship_to_address_id")?,
+        items,
+        bank_details,
+        bank_account_id: row.get("bank_account_id")?,
+        transport_details,
+        place_of_supply: row.get("place_of_supply")?,
+        invoice_date: row.get("invoice_date")?,
+        amount: row.get("amount")?,
+        cgst_percentage: row.get("cgst_percentage")?,
+        sgst_percentage: row.get("sgst_percentage")?,
+        igst_percentage: row.get("igst_percentage")?,
+        additional_charges,
+        discount,
+        total: row.get("total_amount")?,
+        round_off: row.get("round_off")?,
+        rounding_rules,
+        tcs_percentage: row.get("tcs_percentage")?,
+        tcs_amount: row.get("tcs_amount")?,
+        tds_percentage: row.get("tds_percentage")?,
+        tds_expected: row.get("tds_expected")?,
+        supply_type: row.get("supply_type")?,
+        lut_reference: row.get("lut_reference")?,
+        shipping_bill,
+        foreign_currency,
+        total_inr: row.get("total_inr")?,
+        reverse_charge: row.get("reverse_charge")?,
+        company_id: row.get("company_id")?,
+        status: row.get("status")?,
+        document_type: row.get("document_type")?,
+        original_invoice_id: row.get("original_invoice_id")?
+    })
 }
 
 #[command]
@@ -1,3 +1,18 @@
 pub mod invoice;
 pub mod company;
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod export;
+pub mod payment;
+pub mod report;
+pub mod product;
+pub mod stock;
+pub mod quotation;
+pub mod challan;
+pub mod recurring;
+pub mod template;
+pub mod format;
+pub mod state;
+pub mod email;
+pub mod reminder;
+pub mod upi;
+pub mod bank;
\ No newline at end of file
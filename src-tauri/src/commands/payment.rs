@@ -0,0 +1,359 @@
+use chrono::Utc;
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension};
+use serde::Deserialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    db::get_connection,
+    models::{
+        invoice::{DocumentType, InvoiceStatus},
+        money::Money,
+        payment::{CompanyOutstanding, InvoiceBalance, Payment, PaymentAllocation},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct PaymentFilter {
+    pub invoice_id: Option<String>,
+    pub company_id: Option<String>,
+}
+
+/// Records a payment and settles it against one or more invoices. Each invoice's
+/// status moves to partially paid or paid depending on what is still outstanding.
+#[command]
+pub fn record_payment(payment: Payment) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+
+    if payment.amount < Money::ZERO || payment.tds_deducted < Money::ZERO {
+        return Err("Payment amount and TDS cannot be negative".to_string());
+    }
+    if payment.allocations.is_empty() {
+        return Err("A payment must be settled against at least one invoice".to_string());
+    }
+    if payment.allocations.iter().any(|a| a.amount < Money::ZERO || a.tds_amount < Money::ZERO) {
+        return Err("Allocated amounts and TDS cannot be negative".to_string());
+    }
+    // Each line is checked against what the invoice has outstanding, so one line per invoice
+    for (index, allocation) in payment.allocations.iter().enumerate() {
+        if payment.allocations[..index].iter().any(|a| a.invoice_id == allocation.invoice_id) {
+            return Err(format!("Invoice {} is allocated more than once", allocation.invoice_id));
+        }
+    }
+
+    let allocated: Money = payment.allocations.iter().map(|a| a.amount).sum();
+    let allocated_tds: Money = payment.allocations.iter().map(|a| a.tds_amount).sum();
+    if allocated > payment.amount {
+        return Err(format!(
+            "Allocated amount {} exceeds the payment amount {}",
+            allocated, payment.amount
+        ));
+    }
+    if allocated_tds != payment.tds_deducted {
+        return Err(format!(
+            "TDS allocated to invoices ({}) must match the TDS deducted ({})",
+            allocated_tds, payment.tds_deducted
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // A payment belongs to a single client, the one given or else that of the first invoice
+    let mut company_id = payment.company_id.clone();
+    for (index, allocation) in payment.allocations.iter().enumerate() {
+        let (document_type, invoice_company_id): (DocumentType, Option<String>) = tx
+            .query_row(
+                "SELECT document_type, company_id FROM invoice WHERE id = ?1",
+                params![allocation.invoice_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Invoice {} not found", allocation.invoice_id))?;
+        if document_type == DocumentType::CreditNote {
+            return Err("Payments cannot be settled against a credit note".to_string());
+        }
+        if index == 0 && company_id.is_none() {
+            company_id = invoice_company_id.clone();
+        }
+        if invoice_company_id != company_id {
+            return Err("A payment can only settle invoices of one client".to_string());
+        }
+
+        let balance = invoice_balance(&tx, &allocation.invoice_id)?;
+        if balance.status == InvoiceStatus::Cancelled {
+            return Err(format!("Invoice {} is cancelled", balance.invoice_number));
+        }
+        let settling = allocation.amount + allocation.tds_amount;
+        if settling > balance.outstanding {
+            return Err(format!(
+                "Invoice {} has only {} outstanding, cannot settle {}",
+                balance.invoice_number, balance.outstanding, settling
+            ));
+        }
+    }
+
+    let payment_id = Uuid::new_v4().to_string();
+    tx.execute(
+        "INSERT INTO payment (
+        id,
+        company_id,
+        payment_date,
+        amount,
+        mode,
+        reference,
+        tds_deducted,
+        notes,
+        created_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            payment_id,
+            company_id,
+            payment.payment_date,
+            payment.amount,
+            payment.mode,
+            payment.reference,
+            payment.tds_deducted,
+            payment.notes,
+            Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for allocation in &payment.allocations {
+        tx.execute(
+            "INSERT INTO payment_allocation (id, payment_id, invoice_id, amount, tds_amount) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                Uuid::new_v4().to_string(),
+                payment_id,
+                allocation.invoice_id,
+                allocation.amount,
+                allocation.tds_amount
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        refresh_invoice_status(&tx, &allocation.invoice_id)?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(format!("Payment of {} recorded successfully!", payment.amount))
+}
+
+/// Removes a payment and reopens the invoices it had settled.
+#[command]
+pub fn delete_payment(id: String) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let invoice_ids = {
+        let mut stmt = tx
+            .prepare("SELECT invoice_id FROM payment_allocation WHERE payment_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, rusqlite::Error>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    tx.execute("DELETE FROM payment_allocation WHERE payment_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    let deleted = tx
+        .execute("DELETE FROM payment WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Payment {} not found", id));
+    }
+
+    for invoice_id in &invoice_ids {
+        refresh_invoice_status(&tx, invoice_id)?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok("Payment deleted successfully!".to_string())
+}
+
+#[command]
+pub fn get_payments(filter: PaymentFilter) -> Result<Vec<Payment>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let (query, value): (&str, Value) = match (filter.invoice_id, filter.company_id) {
+        (Some(invoice_id), None) => (
+            "SELECT DISTINCT p.* FROM payment p
+             JOIN payment_allocation a ON a.payment_id = p.id
+             WHERE a.invoice_id = ?1 ORDER BY p.payment_date",
+            invoice_id.into(),
+        ),
+        (None, Some(company_id)) => (
+            "SELECT * FROM payment WHERE company_id = ?1 ORDER BY payment_date",
+            company_id.into(),
+        ),
+        (None, None) => return Err("At least one filter must be provided".to_string()),
+        (Some(_), Some(_)) => return Err("Only one filter can be provided at a time".to_string()),
+    };
+
+    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![value], |row| {
+            Ok(Payment {
+                id: row.get("id")?,
+                company_id: row.get("company_id")?,
+                payment_date: row.get("payment_date")?,
+                amount: row.get("amount")?,
+                mode: row.get("mode")?,
+                reference: row.get("reference")?,
+                tds_deducted: row.get("tds_deducted")?,
+                notes: row.get("notes")?,
+                allocations: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut payments = rows
+        .collect::<Result<Vec<Payment>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT invoice_id, amount, tds_amount FROM payment_allocation WHERE payment_id = ?1")
+        .map_err(|e| e.to_string())?;
+    for payment in payments.iter_mut() {
+        payment.allocations = stmt
+            .query_map(params![payment.id], |row| {
+                Ok(PaymentAllocation {
+                    invoice_id: row.get(0)?,
+                    amount: row.get(1)?,
+                    tds_amount: row.get(2)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<PaymentAllocation>, rusqlite::Error>>()
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(payments)
+}
+
+#[command]
+pub fn get_invoice_balance(invoice_id: String) -> Result<InvoiceBalance, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    invoice_balance(&conn, &invoice_id)
+}
+
+#[command]
+pub fn get_company_outstanding(company_id: String) -> Result<CompanyOutstanding, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let invoices = fetch_invoice_balances(
+        &conn,
+        "i.company_id = ?1 AND i.document_type != 'credit_note' AND i.status != 'cancelled'",
+        &[company_id.clone().into()],
+    )?;
+
+    // Credit notes raised against a specific invoice are already netted off in its balance
+    let unadjusted_credit_notes: Money = conn
+        .query_row(
+            "SELECT COALESCE(SUM(total_inr), 0) FROM invoice
+            WHERE company_id = ?1 AND document_type = 'credit_note' AND original_invoice_id IS NULL
+                AND status != 'cancelled'",
+            params![company_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let total_invoiced = invoices.iter().map(|i| i.total).sum();
+    let total_received = invoices.iter().map(|i| i.received).sum();
+    let total_tds = invoices.iter().map(|i| i.tds).sum();
+    let total_credited: Money = invoices.iter().map(|i| i.credited).sum();
+    let outstanding: Money = invoices.iter().map(|i| i.outstanding).sum();
+
+    Ok(CompanyOutstanding {
+        company_id,
+        total_invoiced,
+        total_received,
+        total_tds,
+        total_credited: total_credited + unadjusted_credit_notes,
+        outstanding: outstanding - unadjusted_credit_notes,
+        invoices,
+    })
+}
+
+/// Invoice totals along with everything settled against them (payments, TDS and
+/// credit notes raised on the invoice), for invoices matching `condition`. All in
+/// rupees, whatever currency the invoice was billed in.
+pub(crate) fn fetch_invoice_balances(
+    conn: &Connection,
+    condition: &str,
+    values: &[Value],
+) -> Result<Vec<InvoiceBalance>, String> {
+    let query = format!(
+        "SELECT i.id, i.invoice_number, i.invoice_date, i.total_inr, i.status,
+            CAST(ROUND(i.tds_expected * i.exchange_rate) AS INTEGER) AS tds_expected,
+            COALESCE((SELECT SUM(a.amount) FROM payment_allocation a WHERE a.invoice_id = i.id), 0) AS received,
+            COALESCE((SELECT SUM(a.tds_amount) FROM payment_allocation a WHERE a.invoice_id = i.id), 0) AS tds,
+            {} AS credited
+        FROM invoice i
+        WHERE {}
+        ORDER BY i.invoice_date, i.invoice_number",
+        CREDITED_SQL, condition
+    );
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let total: Money = row.get("total_inr")?;
+            let received: Money = row.get("received")?;
+            let tds: Money = row.get("tds")?;
+            let credited: Money = row.get("credited")?;
+
+            Ok(InvoiceBalance {
+                invoice_id: row.get("id")?,
+                invoice_number: row.get("invoice_number")?,
+                invoice_date: row.get("invoice_date")?,
+                total,
+                received,
+                tds,
+                tds_expected: row.get("tds_expected")?,
+                credited,
+                outstanding: total - received - tds - credited,
+                status: row.get("status")?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let balances: Result<Vec<InvoiceBalance>, rusqlite::Error> = rows.collect();
+    balances.map_err(|e| e.to_string())
+}
+
+/// Total of the credit notes raised against invoice `i`.
+pub(crate) const CREDITED_SQL: &str = "COALESCE((
+    SELECT SUM(cn.total_inr) FROM invoice cn
+    WHERE cn.original_invoice_id = i.id AND cn.document_type = 'credit_note' AND cn.status != 'cancelled'
+), 0)";
+
+pub(crate) fn invoice_balance(conn: &Connection, invoice_id: &str) -> Result<InvoiceBalance, String> {
+    fetch_invoice_balances(conn, "i.id = ?1", &[invoice_id.to_string().into()])?
+        .pop()
+        .ok_or_else(|| format!("Invoice {} not found", invoice_id))
+}
+
+/// Recomputes an invoice's status from the payments and credit notes settled against it.
+pub(crate) fn refresh_invoice_status(conn: &Connection, invoice_id: &str) -> Result<InvoiceStatus, String> {
+    let balance = invoice_balance(conn, invoice_id)?;
+    if balance.status == InvoiceStatus::Cancelled {
+        return Ok(balance.status);
+    }
+    let settled = balance.received + balance.tds + balance.credited;
+    let status = InvoiceStatus::from_settlement(balance.total, settled);
+
+    conn.execute(
+        "UPDATE invoice SET status = ?1 WHERE id = ?2",
+        params![status, invoice_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(status)
+}
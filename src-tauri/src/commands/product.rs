@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{params, params_from_iter, types::Value, Connection, Row};
+use serde::Deserialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    db::get_connection,
+    models::{
+        invoice::InvoiceItem,
+        money::{Money, Percent},
+        product::{Product, ProductKind},
+    },
+};
+
+/// GST slabs a catalog item can carry.
+const GST_RATES: [f64; 10] = [0.0, 0.1, 0.25, 1.5, 3.0, 5.0, 12.0, 18.0, 28.0, 40.0];
+
+#[derive(Deserialize, Debug)]
+pub struct ProductFilter {
+    pub query: Option<String>, // Matches name, description or HSN/SAC
+    pub kind: Option<ProductKind>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[command]
+pub fn add_product(product: Product) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    validate_product(&product)?;
+
+    let now = Utc::now().to_rfc3339();
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO product (
+        id,
+        name,
+        description,
+        hsn_code,
+        unit,
+        default_rate,
+        gst_rate,
+        kind,
+        archived,
+        low_stock_threshold,
+        created_at,
+        updated_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?10, ?10)",
+        params![
+            id,
+            product.name.trim(),
+            product.description,
+            product.hsn_code.trim(),
+            product.unit,
+            product.default_rate,
+            product.gst_rate,
+            product.kind,
+            product.low_stock_threshold,
+            now
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+#[command]
+pub fn update_product(product: Product) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    let id = product.id.as_ref().ok_or("Product id is required for an update")?;
+    validate_product(&product)?;
+
+    let updated = conn
+        .execute(
+            "UPDATE product SET
+            name = ?1,
+            description = ?2,
+            hsn_code = ?3,
+            unit = ?4,
+            default_rate = ?5,
+            gst_rate = ?6,
+            kind = ?7,
+            low_stock_threshold = ?8,
+            updated_at = ?9
+        WHERE id = ?10",
+            params![
+                product.name.trim(),
+                product.description,
+                product.hsn_code.trim(),
+                product.unit,
+                product.default_rate,
+                product.gst_rate,
+                product.kind,
+                product.low_stock_threshold,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Product {} not found", id));
+    }
+    Ok(format!("Product {} updated successfully!", product.name))
+}
+
+/// Archived products stay on old invoices but no longer show up for new ones.
+#[command]
+pub fn archive_product(id: String, archived: bool) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let updated = conn
+        .execute(
+            "UPDATE product SET archived = ?1, updated_at = ?2 WHERE id = ?3",
+            params![archived, Utc::now().to_rfc3339(), id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Product {} not found", id));
+    }
+    Ok(if archived { "Product archived." } else { "Product restored." }.to_string())
+}
+
+/// Catalog lookup used by the document dialog's item autocomplete.
+#[command]
+pub fn search_products(filter: ProductFilter) -> Result<Vec<Product>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT * FROM product WHERE 1=1".to_string();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(text) = filter.query {
+        values.push(format!("%{}%", text.trim()).into());
+        let n = values.len();
+        query.push_str(&format!(" AND (name LIKE ?{n} OR description LIKE ?{n} OR hsn_code LIKE ?{n})"));
+    }
+    if let Some(kind) = filter.kind {
+        values.push(kind.as_str().to_string().into());
+        query.push_str(&format!(" AND kind = ?{}", values.len()));
+    }
+    if !filter.include_archived {
+        query.push_str(" AND archived = 0");
+    }
+    query.push_str(" ORDER BY name");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), product_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let products: Result<Vec<Product>, rusqlite::Error> = rows.collect();
+    products.map_err(|e| e.to_string())
+}
+
+pub(crate) fn product_from_row(row: &Row) -> rusqlite::Result<Product> {
+    Ok(Product {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        description: row.get("description")?,
+        hsn_code: row.get("hsn_code")?,
+        unit: row.get("unit")?,
+        default_rate: row.get("default_rate")?,
+        gst_rate: row.get("gst_rate")?,
+        kind: row.get("kind")?,
+        archived: row.get("archived")?,
+        low_stock_threshold: row.get("low_stock_threshold")?,
+    })
+}
+
+/// Loads the catalog entries referenced by `items`, keyed by product id.
+pub(crate) fn fetch_item_products(conn: &Connection, items: &[InvoiceItem]) -> Result<HashMap<String, Product>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM product WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    let mut products = HashMap::new();
+    for product_id in items.iter().filter_map(|item| item.product_id.as_ref()) {
+        if products.contains_key(product_id) {
+            continue;
+        }
+        let product = stmt
+            .query_row(params![product_id], product_from_row)
+            .map_err(|_| format!("Product {} is not in the catalog", product_id))?;
+        products.insert(product_id.clone(), product);
+    }
+    Ok(products)
+}
+
+/// Items picked from the catalog must still match it: the product has to be active and
+/// the HSN/SAC and unit on the line must be the catalog's. Free-text items are left alone.
+pub(crate) fn validate_items_against_catalog(conn: &Connection, items: &[InvoiceItem]) -> Result<(), String> {
+    let products = fetch_item_products(conn, items)?;
+
+    for item in items {
+        let Some(product) = item.product_id.as_ref().and_then(|id| products.get(id)) else {
+            continue;
+        };
+        if product.archived {
+            return Err(format!("Product {} is archived", product.name));
+        }
+        if item.hsn_code.trim() != product.hsn_code {
+            return Err(format!(
+                "HSN/SAC {} on \"{}\" does not match the catalog ({})",
+                item.hsn_code, item.description, product.hsn_code
+            ));
+        }
+        if !item.unit.eq_ignore_ascii_case(&product.unit) {
+            return Err(format!(
+                "Unit {} on \"{}\" does not match the catalog ({})",
+                item.unit, item.description, product.unit
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_product(product: &Product) -> Result<(), String> {
+    if product.name.trim().is_empty() {
+        return Err("Product name is required".to_string());
+    }
+
+    let code = product.hsn_code.trim();
+    if !code.chars().all(|c| c.is_ascii_digit()) || ![4, 6, 8].contains(&code.len()) {
+        return Err(format!("HSN/SAC code {} must be 4, 6 or 8 digits", code));
+    }
+    // SAC codes all fall under chapter 99, HSN codes never do
+    match product.kind {
+        ProductKind::Services if !code.starts_with("99") => {
+            return Err(format!("SAC code {} for a service must start with 99", code));
+        }
+        ProductKind::Goods if code.starts_with("99") => {
+            return Err(format!("{} is a SAC code, goods need an HSN code", code));
+        }
+        _ => {}
+    }
+
+    if product.unit.trim().is_empty() {
+        return Err("Unit of measure is required".to_string());
+    }
+    if product.default_rate < Money::ZERO {
+        return Err("Default rate cannot be negative".to_string());
+    }
+    if !GST_RATES.iter().any(|rate| Percent::from_f64(*rate) == product.gst_rate) {
+        return Err(format!("{}% is not a valid GST rate", product.gst_rate));
+    }
+    Ok(())
+}
@@ -0,0 +1,310 @@
+use chrono::{Local, Utc};
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
+use serde::Deserialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::{
+        invoice::{allocate_invoice_number, insert_invoice, validate_discounts_and_charges},
+        product::validate_items_against_catalog,
+        user::load_rounding_rules,
+    },
+    db::get_connection,
+    models::{
+        invoice::Invoice,
+        quotation::{Quotation, QuotationKind, QuotationStatus},
+        state::find_state,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct QuotationFilter {
+    pub kind: Option<QuotationKind>,
+    pub status: Option<QuotationStatus>,
+    pub company_id: Option<String>,
+    pub recipient_name: Option<String>,
+}
+
+#[command]
+pub fn create_quotation(quotation: Quotation) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    if quotation.items.is_empty() {
+        return Err("A quotation needs at least one item".to_string());
+    }
+    if quotation.valid_until < quotation.quotation_date {
+        return Err("Valid until date cannot be before the quotation date".to_string());
+    }
+    validate_items_against_catalog(&conn, &quotation.items)?;
+    validate_discounts_and_charges(&quotation.items, quotation.discount, &quotation.additional_charges)?;
+    let place_of_supply = find_state(&quotation.place_of_supply)
+        .ok_or_else(|| format!("{} is not a known place of supply", quotation.place_of_supply))?
+        .code;
+    // Worked out the way the invoice will be, so converting it bills the quoted amount
+    let invoice = Quotation { place_of_supply: place_of_supply.to_string(), ..quotation.clone() }.to_invoice();
+    let totals = invoice.totals(&load_rounding_rules(&conn)?);
+
+    let items_json = serde_json::to_string(&quotation.items).map_err(|e| e.to_string())?;
+    let additional_charges_json = serde_json::to_string(&quotation.additional_charges)
+        .map_err(|e| e.to_string())?;
+    let discount_json = serde_json::to_string(&quotation.discount).map_err(|e| e.to_string())?;
+
+    let company_id = match &quotation.company_id {
+        Some(id) => Some(id.clone()),
+        None => conn
+            .query_row(
+                "SELECT id FROM company WHERE gst_number = ?1 LIMIT 1",
+                params![quotation.recipient_gst_number],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?,
+    };
+
+    conn.execute(
+        "INSERT INTO quotation (
+        id,
+        kind,
+        quotation_number,
+        quotation_date,
+        valid_until,
+        status,
+        company_id,
+        issuer_name,
+        issuer_address,
+        issuer_gst_number,
+        issuer_phone,
+        issuer_email,
+        recipient_name,
+        recipient_address,
+        recipient_gst_number,
+        recipient_phone,
+        recipient_email,
+        place_of_supply,
+        items_json,
+        amount,
+        cgst_percentage,
+        sgst_percentage,
+        igst_percentage,
+        additional_charges_json,
+        total_amount,
+        notes,
+        created_at,
+        discount_json
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28)",
+        params![
+            Uuid::new_v4().to_string(),
+            quotation.kind,
+            quotation.quotation_number,
+            quotation.quotation_date,
+            quotation.valid_until,
+            QuotationStatus::Sent,
+            company_id,
+            quotation.issuer_name,
+            quotation.issuer_address,
+            quotation.issuer_gst_number,
+            quotation.issuer_phone,
+            quotation.issuer_email,
+            quotation.recipient_name,
+            quotation.recipient_address,
+            quotation.recipient_gst_number,
+            quotation.recipient_phone,
+            quotation.recipient_email,
+            place_of_supply,
+            items_json,
+            invoice.items_value(),
+            quotation.cgst_percentage,
+            quotation.sgst_percentage,
+            quotation.igst_percentage,
+            additional_charges_json,
+            totals.total,
+            quotation.notes,
+            Utc::now().to_rfc3339(),
+            discount_json
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "{} {} created successfully!",
+        quotation.kind.label(),
+        quotation.quotation_number
+    ))
+}
+
+/// Records the client's answer. Expired and converted are set by the app itself.
+#[command]
+pub fn update_quotation_status(id: String, status: QuotationStatus) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    if matches!(status, QuotationStatus::Expired | QuotationStatus::Converted) {
+        return Err(format!("Status cannot be set to {} by hand", status.as_str()));
+    }
+
+    let current = fetch_quotation(&conn, &id)?;
+    if current.status == QuotationStatus::Converted {
+        return Err(format!(
+            "{} {} has already been converted",
+            current.kind.label(),
+            current.quotation_number
+        ));
+    }
+
+    conn.execute(
+        "UPDATE quotation SET status = ?1 WHERE id = ?2",
+        params![status, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(format!("{} {} marked as {}.", current.kind.label(), current.quotation_number, status.as_str()))
+}
+
+#[command]
+pub fn search_quotations(filter: QuotationFilter) -> Result<Vec<Quotation>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    expire_quotations(&conn)?;
+
+    let mut query = "SELECT * FROM quotation WHERE 1=1".to_string();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(kind) = filter.kind {
+        values.push(kind.as_str().to_string().into());
+        query.push_str(&format!(" AND kind = ?{}", values.len()));
+    }
+    if let Some(status) = filter.status {
+        values.push(status.as_str().to_string().into());
+        query.push_str(&format!(" AND status = ?{}", values.len()));
+    }
+    if let Some(company_id) = filter.company_id {
+        values.push(company_id.into());
+        query.push_str(&format!(" AND company_id = ?{}", values.len()));
+    }
+    if let Some(name) = filter.recipient_name {
+        values.push(format!("%{}%", name.trim()).into());
+        query.push_str(&format!(" AND recipient_name LIKE ?{}", values.len()));
+    }
+    query.push_str(" ORDER BY quotation_date DESC, quotation_number DESC");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), quotation_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let quotations: Result<Vec<Quotation>, rusqlite::Error> = rows.collect();
+    quotations.map_err(|e| e.to_string())
+}
+
+/// Copies a quotation into a tax invoice numbered from the profile's invoice series.
+/// The invoice is paid into the default bank account and is dated today unless
+/// `invoice_date` is given. Returns the new invoice id.
+#[command]
+pub fn convert_to_invoice(id: String, invoice_date: Option<String>) -> Result<String, String> {
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    expire_quotations(&conn)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let quotation = fetch_quotation(&tx, &id)?;
+    match quotation.status {
+        QuotationStatus::Sent | QuotationStatus::Accepted => {}
+        QuotationStatus::Converted => {
+            return Err(format!(
+                "{} {} has already been converted",
+                quotation.kind.label(),
+                quotation.quotation_number
+            ))
+        }
+        status => {
+            return Err(format!(
+                "{} {} is {} and cannot be invoiced",
+                quotation.kind.label(),
+                quotation.quotation_number,
+                status.as_str()
+            ))
+        }
+    }
+
+    let invoice = Invoice {
+        invoice_number: allocate_invoice_number(&tx)?,
+        invoice_date: invoice_date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string()),
+        ..quotation.to_invoice()
+    };
+    let invoice_id = insert_invoice(&tx, &invoice)?;
+
+    tx.execute(
+        "UPDATE quotation SET status = ?1, converted_invoice_id = ?2 WHERE id = ?3",
+        params![QuotationStatus::Converted, invoice_id, id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(invoice_id)
+}
+
+/// Quotations still waiting on the client lapse once their validity date has passed.
+fn expire_quotations(conn: &Connection) -> Result<(), String> {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    conn.execute(
+        "UPDATE quotation SET status = ?1 WHERE status = ?2 AND valid_until < ?3",
+        params![QuotationStatus::Expired, QuotationStatus::Sent, today],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn fetch_quotation(conn: &Connection, id: &str) -> Result<Quotation, String> {
+    conn.query_row("SELECT * FROM quotation WHERE id = ?1", params![id], quotation_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Quotation {} not found", id))
+}
+
+fn quotation_from_row(row: &Row) -> rusqlite::Result<Quotation> {
+    let items_json: String = row.get("items_json")?;
+    let additional_charges_json: Option<String> = row.get("additional_charges_json")?;
+    let discount_json: Option<String> = row.get("discount_json")?;
+    let items = serde_json::from_str(&items_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    let additional_charges = match additional_charges_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+    let discount = match discount_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?,
+        None => None,
+    };
+
+    Ok(Quotation {
+        id: row.get("id")?,
+        kind: row.get("kind")?,
+        quotation_number: row.get("quotation_number")?,
+        quotation_date: row.get("quotation_date")?,
+        valid_until: row.get("valid_until")?,
+        status: row.get("status")?,
+        company_id: row.get("company_id")?,
+        issuer_name: row.get("issuer_name")?,
+        issuer_address: row.get("issuer_address")?,
+        issuer_gst_number: row.get("issuer_gst_number")?,
+        issuer_phone: row.get("issuer_phone")?,
+        issuer_email: row.get("issuer_email")?,
+        recipient_name: row.get("recipient_name")?,
+        recipient_address: row.get("recipient_address")?,
+        recipient_gst_number: row.get("recipient_gst_number")?,
+        recipient_phone: row.get("recipient_phone")?,
+        recipient_email: row.get("recipient_email")?,
+        place_of_supply: row.get("place_of_supply")?,
+        items,
+        amount: row.get("amount")?,
+        cgst_percentage: row.get("cgst_percentage")?,
+        sgst_percentage: row.get("sgst_percentage")?,
+        igst_percentage: row.get("igst_percentage")?,
+        additional_charges,
+        discount,
+        total: row.get("total_amount")?,
+        notes: row.get("notes")?,
+        converted_invoice_id: row.get("converted_invoice_id")?,
+    })
+}
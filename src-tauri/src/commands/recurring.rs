@@ -0,0 +1,459 @@
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::{
+        invoice::{allocate_invoice_number, insert_invoice},
+        user::load_rounding_rules,
+    },
+    db::get_connection,
+    models::{
+        invoice::{DocumentType, Invoice, InvoiceStatus},
+        recurring::{RecurringRunLog, RecurringSchedule, UpcomingRun},
+    },
+};
+
+/// Upcoming runs shown per schedule when the preview doesn't ask for a number.
+const DEFAULT_PREVIEW_RUNS: u32 = 3;
+/// Failed attempts at one run before the schedule is paused for the user to fix.
+const MAX_FAILED_RUNS: i64 = 3;
+
+#[command]
+pub fn add_recurring_schedule(schedule: RecurringSchedule) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    let next_run_date = validate_schedule(&conn, &schedule)?;
+    let template_json = serde_json::to_string(&schedule.template).map_err(|e| e.to_string())?;
+
+    let now = Utc::now().to_rfc3339();
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO recurring_schedule (
+        id,
+        name,
+        company_id,
+        frequency,
+        start_date,
+        end_date,
+        day_of_month,
+        next_run_date,
+        active,
+        template_json,
+        created_at,
+        updated_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)",
+        params![
+            id,
+            schedule.name.trim(),
+            schedule.company_id,
+            schedule.frequency,
+            schedule.start_date,
+            schedule.end_date,
+            schedule.day_of_month,
+            next_run_date,
+            schedule.active,
+            template_json,
+            now
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+/// Replaces a schedule. Its next run is worked out again from the new dates, never
+/// earlier than the run after the last invoice it already generated.
+#[command]
+pub fn update_recurring_schedule(schedule: RecurringSchedule) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    let id = schedule.id.as_ref().ok_or("Schedule id is required for an update")?;
+    let mut next_run_date = validate_schedule(&conn, &schedule)?;
+    let template_json = serde_json::to_string(&schedule.template).map_err(|e| e.to_string())?;
+
+    let last_run: Option<String> = conn
+        .query_row(
+            "SELECT MAX(run_date) FROM recurring_run_log WHERE schedule_id = ?1 AND invoice_id IS NOT NULL",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if let (Some(last_run), Some(next)) = (last_run, next_run_date.as_ref()) {
+        if *next <= last_run {
+            let last_run = parse_date(&last_run)?;
+            next_run_date = run_after(&schedule, last_run)?.map(|d| d.to_string());
+        }
+    }
+
+    let updated = conn
+        .execute(
+            "UPDATE recurring_schedule SET
+            name = ?1,
+            company_id = ?2,
+            frequency = ?3,
+            start_date = ?4,
+            end_date = ?5,
+            day_of_month = ?6,
+            next_run_date = ?7,
+            active = ?8,
+            template_json = ?9,
+            updated_at = ?10
+        WHERE id = ?11",
+            params![
+                schedule.name.trim(),
+                schedule.company_id,
+                schedule.frequency,
+                schedule.start_date,
+                schedule.end_date,
+                schedule.day_of_month,
+                next_run_date,
+                schedule.active,
+                template_json,
+                Utc::now().to_rfc3339(),
+                id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Schedule {} not found", id));
+    }
+    Ok(format!("Schedule {} updated successfully!", schedule.name))
+}
+
+/// Paused schedules keep their next run date; resuming raises anything missed meanwhile
+/// on the next scheduler run.
+#[command]
+pub fn set_recurring_schedule_active(id: String, active: bool) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let updated = conn
+        .execute(
+            "UPDATE recurring_schedule SET active = ?1, updated_at = ?2 WHERE id = ?3",
+            params![active, Utc::now().to_rfc3339(), id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err(format!("Schedule {} not found", id));
+    }
+    Ok(if active { "Schedule resumed." } else { "Schedule paused." }.to_string())
+}
+
+#[command]
+pub fn get_recurring_schedules() -> Result<Vec<RecurringSchedule>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    fetch_schedules(&conn, false)
+}
+
+/// Raises every invoice that has fallen due up to `as_of` (today by default) and returns
+/// what happened, one log entry per invoice or failure.
+#[command]
+pub fn run_recurring_invoices(as_of: Option<String>) -> Result<Vec<RecurringRunLog>, String> {
+    let as_of = match as_of {
+        Some(date) => parse_date(&date)?,
+        None => Local::now().date_naive(),
+    };
+    let mut conn = get_connection().map_err(|e| e.to_string())?;
+    generate_due_invoices(&mut conn, as_of)
+}
+
+/// Scheduler entry point used on app start and login.
+pub fn run_due_recurring_invoices() -> Result<Vec<RecurringRunLog>, String> {
+    run_recurring_invoices(None)
+}
+
+#[command]
+pub fn get_recurring_run_log(schedule_id: Option<String>) -> Result<Vec<RecurringRunLog>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT l.*, s.name AS schedule_name FROM recurring_run_log l
+        JOIN recurring_schedule s ON s.id = l.schedule_id"
+        .to_string();
+    if schedule_id.is_some() {
+        query.push_str(" WHERE l.schedule_id = ?1");
+    }
+    query.push_str(" ORDER BY l.created_at DESC");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = match &schedule_id {
+        Some(id) => stmt.query_map(params![id], run_log_from_row),
+        None => stmt.query_map([], run_log_from_row),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let log: Result<Vec<RecurringRunLog>, rusqlite::Error> = rows.collect();
+    log.map_err(|e| e.to_string())
+}
+
+/// Lists the next `runs` invoices each active schedule will raise, soonest first.
+#[command]
+pub fn preview_recurring_runs(schedule_id: Option<String>, runs: Option<u32>) -> Result<Vec<UpcomingRun>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    let runs = runs.unwrap_or(DEFAULT_PREVIEW_RUNS);
+    let rules = load_rounding_rules(&conn)?;
+
+    let mut upcoming = Vec::new();
+    for schedule in fetch_schedules(&conn, true)? {
+        let schedule_id_value = schedule.id.clone().unwrap_or_default();
+        if schedule_id.as_ref().is_some_and(|id| *id != schedule_id_value) {
+            continue;
+        }
+
+        let total = schedule.template.totals(&rules).total;
+        let mut next = schedule.next_run_date.as_deref().map(parse_date).transpose()?;
+        for _ in 0..runs {
+            let Some(run_date) = next else { break };
+            upcoming.push(UpcomingRun {
+                schedule_id: schedule_id_value.clone(),
+                schedule_name: schedule.name.clone(),
+                company_id: schedule.company_id.clone(),
+                recipient_name: schedule.template.recipient_name.clone(),
+                run_date: run_date.to_string(),
+                total,
+            });
+            next = run_after(&schedule, run_date)?;
+        }
+    }
+
+    upcoming.sort_by(|a, b| a.run_date.cmp(&b.run_date));
+    Ok(upcoming)
+}
+
+fn generate_due_invoices(conn: &mut Connection, as_of: NaiveDate) -> Result<Vec<RecurringRunLog>, String> {
+    let mut log = Vec::new();
+
+    for schedule in fetch_schedules(conn, true)? {
+        let schedule_id = schedule.id.clone().unwrap_or_default();
+        let mut next = schedule.next_run_date.as_deref().map(parse_date).transpose()?;
+
+        while let Some(run_date) = next.filter(|date| *date <= as_of) {
+            let following = run_after(&schedule, run_date)?;
+            let now = Utc::now().to_rfc3339();
+            let mut entry = RecurringRunLog {
+                id: Uuid::new_v4().to_string(),
+                schedule_id: schedule_id.clone(),
+                schedule_name: schedule.name.clone(),
+                run_date: run_date.to_string(),
+                invoice_id: None,
+                invoice_number: None,
+                error: None,
+                created_at: now,
+            };
+
+            match generate_invoice(conn, &schedule, run_date, following) {
+                Ok((invoice_id, invoice_number)) => {
+                    entry.invoice_id = Some(invoice_id);
+                    entry.invoice_number = Some(invoice_number);
+                    next = following;
+                }
+                Err(e) => {
+                    // Leave the run due so it is retried on the next scheduler run, unless it keeps failing
+                    entry.error = Some(if failed_attempts(conn, &schedule_id, run_date)? + 1 >= MAX_FAILED_RUNS {
+                        pause_schedule(conn, &schedule_id)?;
+                        format!("{} (schedule paused after {} failed attempts)", e, MAX_FAILED_RUNS)
+                    } else {
+                        e
+                    });
+                    next = None;
+                }
+            }
+
+            insert_run_log(conn, &entry)?;
+            log.push(entry);
+        }
+    }
+
+    Ok(log)
+}
+
+/// Failed attempts at the run since the schedule was last saved or resumed.
+fn failed_attempts(conn: &Connection, schedule_id: &str, run_date: NaiveDate) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM recurring_run_log l JOIN recurring_schedule s ON s.id = l.schedule_id
+        WHERE l.schedule_id = ?1 AND l.run_date = ?2 AND l.error IS NOT NULL AND l.created_at >= s.updated_at",
+        params![schedule_id, run_date.to_string()],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn pause_schedule(conn: &Connection, schedule_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE recurring_schedule SET active = 0, updated_at = ?1 WHERE id = ?2",
+        params![Utc::now().to_rfc3339(), schedule_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Raises one invoice from the schedule's template and moves the schedule on, all or nothing.
+fn generate_invoice(
+    conn: &mut Connection,
+    schedule: &RecurringSchedule,
+    run_date: NaiveDate,
+    following: Option<NaiveDate>,
+) -> Result<(String, String), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let invoice = Invoice {
+        id: None,
+        invoice_number: allocate_invoice_number(&tx)?,
+        invoice_date: run_date.to_string(),
+        company_id: Some(schedule.company_id.clone()),
+        status: InvoiceStatus::Unpaid,
+        document_type: DocumentType::TaxInvoice,
+        original_invoice_id: None,
+        ..schedule.template.clone()
+    };
+    let invoice_id = insert_invoice(&tx, &invoice)?;
+
+    tx.execute(
+        "UPDATE recurring_schedule SET next_run_date = ?1 WHERE id = ?2",
+        params![following.map(|d| d.to_string()), schedule.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok((invoice_id, invoice.invoice_number))
+}
+
+fn insert_run_log(conn: &Connection, entry: &RecurringRunLog) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO recurring_run_log (
+        id,
+        schedule_id,
+        run_date,
+        invoice_id,
+        invoice_number,
+        error,
+        created_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            entry.id,
+            entry.schedule_id,
+            entry.run_date,
+            entry.invoice_id,
+            entry.invoice_number,
+            entry.error,
+            entry.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Checks the schedule and returns its first run date, `None` if it ends before any run.
+fn validate_schedule(conn: &Connection, schedule: &RecurringSchedule) -> Result<Option<String>, String> {
+    if schedule.name.trim().is_empty() {
+        return Err("Schedule name is required".to_string());
+    }
+    if !(1..=31).contains(&schedule.day_of_month) {
+        return Err("Day of month must be between 1 and 31".to_string());
+    }
+    if schedule.template.items.is_empty() {
+        return Err("A recurring invoice needs at least one item".to_string());
+    }
+
+    let start_date = parse_date(&schedule.start_date)?;
+    if let Some(end_date) = &schedule.end_date {
+        if parse_date(end_date)? < start_date {
+            return Err("End date cannot be before the start date".to_string());
+        }
+    }
+
+    let company_exists = conn
+        .query_row("SELECT 1 FROM company WHERE id = ?1", params![schedule.company_id], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if !company_exists {
+        return Err(format!("Client {} not found", schedule.company_id));
+    }
+
+    let first = occurrence(start_date.year(), start_date.month(), schedule.day_of_month);
+    let first = if first < start_date {
+        let (year, month) = add_months(start_date.year(), start_date.month(), 1);
+        occurrence(year, month, schedule.day_of_month)
+    } else {
+        first
+    };
+    Ok(within_end(schedule, first)?.map(|d| d.to_string()))
+}
+
+/// The run one period after `run_date`, or `None` once the schedule has ended.
+fn run_after(schedule: &RecurringSchedule, run_date: NaiveDate) -> Result<Option<NaiveDate>, String> {
+    let (year, month) = add_months(run_date.year(), run_date.month(), schedule.frequency.months());
+    within_end(schedule, occurrence(year, month, schedule.day_of_month))
+}
+
+fn within_end(schedule: &RecurringSchedule, date: NaiveDate) -> Result<Option<NaiveDate>, String> {
+    match &schedule.end_date {
+        Some(end_date) if date > parse_date(end_date)? => Ok(None),
+        _ => Ok(Some(date)),
+    }
+}
+
+fn add_months(year: i32, month: u32, months: u32) -> (i32, u32) {
+    let index = year * 12 + month as i32 - 1 + months as i32;
+    (index / 12, (index % 12) as u32 + 1)
+}
+
+/// `day` of the given month, or the month's last day when it is shorter.
+fn occurrence(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap_or_default()
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date {}", date))
+}
+
+fn fetch_schedules(conn: &Connection, active_only: bool) -> Result<Vec<RecurringSchedule>, String> {
+    let query = if active_only {
+        "SELECT * FROM recurring_schedule WHERE active = 1 AND next_run_date IS NOT NULL ORDER BY name"
+    } else {
+        "SELECT * FROM recurring_schedule ORDER BY name"
+    };
+
+    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], schedule_from_row).map_err(|e| e.to_string())?;
+
+    let schedules: Result<Vec<RecurringSchedule>, rusqlite::Error> = rows.collect();
+    schedules.map_err(|e| e.to_string())
+}
+
+fn schedule_from_row(row: &Row) -> rusqlite::Result<RecurringSchedule> {
+    let template_json: String = row.get("template_json")?;
+    let template = serde_json::from_str(&template_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+
+    Ok(RecurringSchedule {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        company_id: row.get("company_id")?,
+        frequency: row.get("frequency")?,
+        start_date: row.get("start_date")?,
+        end_date: row.get("end_date")?,
+        day_of_month: row.get("day_of_month")?,
+        next_run_date: row.get("next_run_date")?,
+        active: row.get("active")?,
+        template,
+    })
+}
+
+fn run_log_from_row(row: &Row) -> rusqlite::Result<RecurringRunLog> {
+    Ok(RecurringRunLog {
+        id: row.get("id")?,
+        schedule_id: row.get("schedule_id")?,
+        schedule_name: row.get("schedule_name")?,
+        run_date: row.get("run_date")?,
+        invoice_id: row.get("invoice_id")?,
+        invoice_number: row.get("invoice_number")?,
+        error: row.get("error")?,
+        created_at: row.get("created_at")?,
+    })
+}
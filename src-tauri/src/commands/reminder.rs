@@ -0,0 +1,404 @@
+use chrono::{Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, Row};
+use tauri::{command, AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::{
+    commands::{
+        email::{invoice_placeholders, invoice_recipients, load_email_settings, render_template, send_email},
+        invoice::{fetch_invoices, invoice_rounding_rules},
+        payment::CREDITED_SQL,
+        upi::invoice_upi_uri,
+    },
+    db::get_connection,
+    models::{
+        email::EmailSettings,
+        invoice::Invoice,
+        money::Money,
+        reminder::{ReminderLogEntry, ReminderRule, ReminderStatus, DEFAULT_REMINDER_BODY_TEMPLATE, DEFAULT_REMINDER_SUBJECT_TEMPLATE},
+    },
+    utils::{
+        amount::format_indian,
+        invoice_pdf::invoice_pdf,
+        table::format_date,
+    },
+};
+
+/// Adds the rule when it has no id, otherwise updates it. Returns the rule's id.
+#[command]
+pub fn save_reminder_rule(rule: ReminderRule) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    if rule.name.trim().is_empty() {
+        return Err("Reminder name is required".to_string());
+    }
+    if !(-90..=365).contains(&rule.days_after_due) {
+        return Err("Reminders can be from 90 days before to 365 days after the due date".to_string());
+    }
+    if rule.repeat_every_days == Some(0) {
+        return Err("Reminders can repeat at most once a day".to_string());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let subject_template = rule.subject_template.as_deref().filter(|template| !template.trim().is_empty());
+    let body_template = rule.body_template.as_deref().filter(|template| !template.trim().is_empty());
+
+    match &rule.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE reminder_rule SET name = ?1, days_after_due = ?2, repeat_every_days = ?3, active = ?4,
+                        subject_template = ?5, body_template = ?6, updated_at = ?7
+                    WHERE id = ?8",
+                    params![rule.name.trim(), rule.days_after_due, rule.repeat_every_days, rule.active, subject_template, body_template, now, id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Reminder {} not found", id));
+            }
+            Ok(id.clone())
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO reminder_rule (id, name, days_after_due, repeat_every_days, active, subject_template,
+                    body_template, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+                params![id, rule.name.trim(), rule.days_after_due, rule.repeat_every_days, rule.active, subject_template, body_template, now],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+    }
+}
+
+/// Removes the rule. Reminders it already sent stay in the history.
+#[command]
+pub fn delete_reminder_rule(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn
+        .execute("DELETE FROM reminder_rule WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Reminder {} not found", id));
+    }
+
+    Ok("Reminder deleted successfully.".to_string())
+}
+
+#[command]
+pub fn get_reminder_rules() -> Result<Vec<ReminderRule>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    fetch_rules(&conn, false)
+}
+
+/// Emails the reminders that have come up for unpaid invoices as of `as_of` (today by
+/// default). An invoice gets at most one reminder per run, the most recent one due; any
+/// older ones it missed are marked skipped. Returns what was sent, failed or skipped.
+#[command]
+pub fn run_payment_reminders(as_of: Option<String>) -> Result<Vec<ReminderLogEntry>, String> {
+    let as_of = match as_of {
+        Some(date) => parse_date(&date)?,
+        None => Local::now().date_naive(),
+    };
+    let conn = get_connection().map_err(|e| e.to_string())?;
+    send_due_reminders(&conn, as_of)
+}
+
+/// Event carrying the history entries of a scheduled reminder run.
+pub const PAYMENT_REMINDERS_EVENT: &str = "payment-reminders-run";
+
+/// A reminder that failed this many times for an invoice is not tried again.
+const MAX_FAILED_ATTEMPTS: i64 = 3;
+
+/// Scheduler entry point used on app start and login.
+pub fn run_due_payment_reminders() -> Result<Vec<ReminderLogEntry>, String> {
+    run_payment_reminders(None)
+}
+
+/// Runs the scheduler on its own thread so a slow mail server doesn't hold up start-up or
+/// login, then emits what was sent as [`PAYMENT_REMINDERS_EVENT`].
+pub fn spawn_due_payment_reminders(app: AppHandle) {
+    std::thread::spawn(move || match run_due_payment_reminders() {
+        Ok(log) => {
+            if let Err(e) = app.emit(PAYMENT_REMINDERS_EVENT, log) {
+                log::warn!("Payment reminder results were not reported: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Payment reminders were not sent: {}", e),
+    });
+}
+
+#[command]
+pub fn get_reminder_log(invoice_id: Option<String>) -> Result<Vec<ReminderLogEntry>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT l.*, COALESCE(r.name, '') AS rule_name, i.invoice_number FROM reminder_log l
+        JOIN invoice i ON i.id = l.invoice_id
+        LEFT JOIN reminder_rule r ON r.id = l.rule_id"
+        .to_string();
+    if invoice_id.is_some() {
+        query.push_str(" WHERE l.invoice_id = ?1");
+    }
+    query.push_str(" ORDER BY l.created_at DESC");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = match &invoice_id {
+        Some(id) => stmt.query_map(params![id], reminder_log_from_row),
+        None => stmt.query_map([], reminder_log_from_row),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let log: Result<Vec<ReminderLogEntry>, rusqlite::Error> = rows.collect();
+    log.map_err(|e| e.to_string())
+}
+
+fn send_due_reminders(conn: &Connection, as_of: NaiveDate) -> Result<Vec<ReminderLogEntry>, String> {
+    let rules = fetch_rules(conn, true)?;
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    let settings = load_email_settings(conn)?.ok_or("Set up the mail server before sending reminders")?;
+
+    let mut log = Vec::new();
+    for (invoice_id, invoice_number, due_date, outstanding) in unpaid_invoices(conn, as_of)? {
+        let mut pending = Vec::new();
+        for rule in &rules {
+            let Some(date) = reminder_date(rule, due_date, as_of) else { continue };
+            if !already_handled(conn, rule, &invoice_id, date)? {
+                pending.push((date, rule));
+            }
+        }
+        pending.sort_by_key(|(date, rule)| (*date, rule.days_after_due));
+        let Some((date, rule)) = pending.pop() else { continue };
+
+        for (skipped_date, skipped_rule) in pending {
+            let entry = log_entry(skipped_rule, &invoice_id, &invoice_number, skipped_date, Vec::new(), Err(None));
+            insert_reminder_log(conn, &entry)?;
+            log.push(entry);
+        }
+
+        let invoice = fetch_invoices(conn, "SELECT * FROM invoice WHERE id = ?1", &[invoice_id.clone().into()])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+        let recipients = invoice_recipients(conn, &invoice, true)?;
+        // Nothing to retry until the client has an address, so the reminder is skipped
+        let entry = if recipients.is_empty() {
+            ReminderLogEntry {
+                error: Some(format!("No email address to remind about {}", invoice_number)),
+                ..log_entry(rule, &invoice_id, &invoice_number, date, recipients, Err(None))
+            }
+        } else {
+            let result = send_reminder(conn, &settings, rule, &invoice, &recipients, due_date, as_of, outstanding);
+            log_entry(rule, &invoice_id, &invoice_number, date, recipients, result.map_err(Some))
+        };
+        insert_reminder_log(conn, &entry)?;
+        log.push(entry);
+    }
+
+    Ok(log)
+}
+
+/// The latest day on or before `as_of` the rule asks for a reminder on, if any. Reminders
+/// meant for before the due date are dropped once it has passed.
+fn reminder_date(rule: &ReminderRule, due_date: NaiveDate, as_of: NaiveDate) -> Option<NaiveDate> {
+    let first = due_date + Duration::days(rule.days_after_due as i64);
+    if first > as_of {
+        return None;
+    }
+    let date = match rule.repeat_every_days {
+        Some(every) => first + Duration::days((as_of - first).num_days() / every as i64 * every as i64),
+        None => first,
+    };
+    if date < due_date && as_of >= due_date {
+        return None;
+    }
+    Some(date)
+}
+
+/// Whether this reminder was already sent (or skipped) for the invoice, or has failed too
+/// often to try again.
+fn already_handled(conn: &Connection, rule: &ReminderRule, invoice_id: &str, date: NaiveDate) -> Result<bool, String> {
+    let (handled, failed): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*) FILTER (WHERE status != 'failed'), COUNT(*) FILTER (WHERE status = 'failed')
+            FROM reminder_log WHERE rule_id = ?1 AND invoice_id = ?2 AND reminder_date = ?3",
+            params![rule.id, invoice_id, date.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(handled > 0 || failed >= MAX_FAILED_ATTEMPTS)
+}
+
+/// Id, number, due date and outstanding amount (in rupees) of every invoice not yet
+/// settled. The due date is the invoice date plus the client's payment terms.
+fn unpaid_invoices(conn: &Connection, as_of: NaiveDate) -> Result<Vec<(String, String, NaiveDate, Money)>, String> {
+    let query = format!(
+        "SELECT i.id, i.invoice_number, i.invoice_date, c.payment_terms_days,
+            i.total_inr - COALESCE((
+                SELECT SUM(a.amount + a.tds_amount) FROM payment_allocation a WHERE a.invoice_id = i.id
+            ), 0) - {} AS outstanding
+        FROM invoice i
+        LEFT JOIN company c ON c.id = i.company_id
+        WHERE i.status NOT IN ('paid', 'cancelled') AND i.document_type != 'credit_note' AND i.invoice_date <= ?1
+        ORDER BY i.invoice_date, i.invoice_number",
+        CREDITED_SQL
+    );
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![as_of.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<u32>>(3)?,
+                row.get::<_, Money>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut invoices = Vec::new();
+    for (id, invoice_number, invoice_date, terms, outstanding) in rows {
+        if outstanding <= Money::ZERO {
+            continue;
+        }
+        let due_date = parse_date(&invoice_date)? + Duration::days(terms.unwrap_or(0) as i64);
+        invoices.push((id, invoice_number, due_date, outstanding));
+    }
+    Ok(invoices)
+}
+
+/// Renders and emails one reminder with the invoice attached.
+#[allow(clippy::too_many_arguments)]
+fn send_reminder(
+    conn: &Connection,
+    settings: &EmailSettings,
+    rule: &ReminderRule,
+    invoice: &Invoice,
+    recipients: &[String],
+    due_date: NaiveDate,
+    as_of: NaiveDate,
+    outstanding: Money,
+) -> Result<(), String> {
+    let totals = invoice.totals(&invoice_rounding_rules(conn, invoice)?);
+    let mut placeholders = invoice_placeholders(invoice, &totals);
+    placeholders.extend([
+        ("due_date", format_date(&due_date.to_string())),
+        ("days_overdue", (as_of - due_date).num_days().max(0).to_string()),
+        ("outstanding", format!("Rs. {}", format_indian(outstanding.to_rupees()))),
+    ]);
+    let subject = render_template(rule.subject_template.as_deref().unwrap_or(DEFAULT_REMINDER_SUBJECT_TEMPLATE), &placeholders);
+    let body = render_template(rule.body_template.as_deref().unwrap_or(DEFAULT_REMINDER_BODY_TEMPLATE), &placeholders);
+    let upi_uri = invoice_upi_uri(conn, invoice, outstanding)?;
+    let attachment = (format!("{}.pdf", invoice.invoice_number.replace(['/', '\\'], "-")), invoice_pdf(invoice, &totals, upi_uri.as_deref())?);
+
+    send_email(settings, recipients, &subject, &body, Some(attachment))
+}
+
+/// A history entry. `Err(None)` marks a skipped reminder, `Err(Some(_))` a failed one.
+fn log_entry(
+    rule: &ReminderRule,
+    invoice_id: &str,
+    invoice_number: &str,
+    date: NaiveDate,
+    recipients: Vec<String>,
+    result: Result<(), Option<String>>,
+) -> ReminderLogEntry {
+    let (status, error) = match result {
+        Ok(()) => (ReminderStatus::Sent, None),
+        Err(Some(e)) => (ReminderStatus::Failed, Some(e)),
+        Err(None) => (ReminderStatus::Skipped, None),
+    };
+
+    ReminderLogEntry {
+        id: Uuid::new_v4().to_string(),
+        rule_id: rule.id.clone().unwrap_or_default(),
+        rule_name: rule.name.clone(),
+        invoice_id: invoice_id.to_string(),
+        invoice_number: invoice_number.to_string(),
+        reminder_date: date.to_string(),
+        recipients,
+        status,
+        error,
+        created_at: Utc::now().to_rfc3339(),
+    }
+}
+
+fn insert_reminder_log(conn: &Connection, entry: &ReminderLogEntry) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO reminder_log (
+        id,
+        rule_id,
+        invoice_id,
+        reminder_date,
+        recipients,
+        status,
+        error,
+        created_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            entry.id,
+            entry.rule_id,
+            entry.invoice_id,
+            entry.reminder_date,
+            entry.recipients.join(", "),
+            entry.status,
+            entry.error,
+            entry.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date {}", date))
+}
+
+fn fetch_rules(conn: &Connection, active_only: bool) -> Result<Vec<ReminderRule>, String> {
+    let query = if active_only {
+        "SELECT * FROM reminder_rule WHERE active = 1 ORDER BY days_after_due"
+    } else {
+        "SELECT * FROM reminder_rule ORDER BY days_after_due"
+    };
+
+    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], rule_from_row).map_err(|e| e.to_string())?;
+
+    let rules: Result<Vec<ReminderRule>, rusqlite::Error> = rows.collect();
+    rules.map_err(|e| e.to_string())
+}
+
+fn rule_from_row(row: &Row) -> rusqlite::Result<ReminderRule> {
+    Ok(ReminderRule {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        days_after_due: row.get("days_after_due")?,
+        repeat_every_days: row.get("repeat_every_days")?,
+        active: row.get("active")?,
+        subject_template: row.get("subject_template")?,
+        body_template: row.get("body_template")?,
+    })
+}
+
+fn reminder_log_from_row(row: &Row) -> rusqlite::Result<ReminderLogEntry> {
+    let recipients: String = row.get("recipients")?;
+
+    Ok(ReminderLogEntry {
+        id: row.get("id")?,
+        rule_id: row.get("rule_id")?,
+        rule_name: row.get("rule_name")?,
+        invoice_id: row.get("invoice_id")?,
+        invoice_number: row.get("invoice_number")?,
+        reminder_date: row.get("reminder_date")?,
+        recipients: recipients.split(", ").filter(|email| !email.is_empty()).map(str::to_string).collect(),
+        status: row.get("status")?,
+        error: row.get("error")?,
+        created_at: row.get("created_at")?,
+    })
+}
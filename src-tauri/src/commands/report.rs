@@ -0,0 +1,354 @@
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use serde::Deserialize;
+use tauri::command;
+
+use crate::{
+    commands::payment::CREDITED_SQL,
+    db::get_connection,
+    models::{
+        invoice::DocumentType,
+        money::Money,
+        report::{AgeingBasis, AgeingReport, AgeingRow, ClientLedger, LedgerEntry, LedgerEntryKind},
+    },
+    utils::{
+        amount::amount_in_words,
+        table::{format_date, write_tables, Cell, ExportFormat, Table},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct LedgerOptions {
+    pub company_id: String,
+    pub from_date: String, // YYYY-MM-DD, inclusive
+    pub to_date: String,   // YYYY-MM-DD, inclusive
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AgeingOptions {
+    pub as_of: Option<String>, // YYYY-MM-DD, defaults to today
+    #[serde(default)]
+    pub basis: AgeingBasis,
+    pub company_id: Option<String>,
+}
+
+/// Buckets every unpaid invoice amount per client by how many days old it is. Credit the
+/// client has not used up, from credit notes raised without an invoice or for more than
+/// was left on theirs, is shown apart and taken off the total.
+#[command]
+pub fn get_ageing_report(options: AgeingOptions) -> Result<AgeingReport, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let as_of = match &options.as_of {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string())?,
+        None => Local::now().date_naive(),
+    };
+
+    // Credit notes set against an invoice are netted off it, the rest count as negative
+    let mut query = format!(
+        "SELECT i.invoice_date, i.company_id, i.recipient_name, c.name, c.payment_terms_days,
+            CASE WHEN i.document_type = 'credit_note' THEN -i.total_inr ELSE i.total_inr - COALESCE((
+                SELECT SUM(a.amount + a.tds_amount) FROM payment_allocation a WHERE a.invoice_id = i.id
+            ), 0) - {} END AS outstanding
+        FROM invoice i
+        LEFT JOIN company c ON c.id = i.company_id
+        WHERE i.status != 'cancelled' AND i.invoice_date <= ?1
+            AND (i.document_type != 'credit_note' OR i.original_invoice_id IS NULL)",
+        CREDITED_SQL
+    );
+    let mut values: Vec<Value> = vec![as_of.format("%Y-%m-%d").to_string().into()];
+    if let Some(company_id) = &options.company_id {
+        values.push(company_id.clone().into());
+        query.push_str(" AND i.company_id = ?2");
+    }
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<u32>>(4)?,
+                row.get::<_, Money>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    // Keyed by name first so the report comes out alphabetically
+    let mut by_company: BTreeMap<(String, Option<String>), AgeingRow> = BTreeMap::new();
+
+    for (invoice_date, company_id, recipient_name, company_name, terms, outstanding) in rows {
+        if outstanding == Money::ZERO {
+            continue;
+        }
+
+        let invoice_date = NaiveDate::parse_from_str(&invoice_date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let start = match options.basis {
+            AgeingBasis::InvoiceDate => invoice_date,
+            AgeingBasis::DueDate => invoice_date + Duration::days(terms.unwrap_or(0) as i64),
+        };
+        let days = (as_of - start).num_days();
+
+        let name = company_name.unwrap_or(recipient_name);
+        let row = by_company
+            .entry((name.clone(), company_id.clone()))
+            .or_insert_with(|| AgeingRow {
+                company_id,
+                company_name: name,
+                ..Default::default()
+            });
+        if outstanding < Money::ZERO {
+            row.credit -= outstanding;
+            row.total += outstanding;
+        } else {
+            add_to_bucket(row, days, outstanding);
+        }
+    }
+
+    let rows: Vec<AgeingRow> = by_company.into_values().collect();
+    let mut totals = AgeingRow {
+        company_name: "Total".to_string(),
+        ..Default::default()
+    };
+    for row in &rows {
+        totals.days_0_30 += row.days_0_30;
+        totals.days_31_60 += row.days_31_60;
+        totals.days_61_90 += row.days_61_90;
+        totals.days_over_90 += row.days_over_90;
+        totals.credit += row.credit;
+        totals.total += row.total;
+    }
+    Ok(AgeingReport {
+        as_of: as_of.format("%Y-%m-%d").to_string(),
+        basis: options.basis,
+        rows,
+        totals,
+    })
+}
+
+#[command]
+pub fn export_ageing_report(options: AgeingOptions, format: ExportFormat, path: String) -> Result<Vec<String>, String> {
+    let report = get_ageing_report(options)?;
+
+    let mut table = Table::new("Receivables Ageing", &["Client", "0-30 Days", "31-60 Days", "61-90 Days", "90+ Days", "Credit", "Total"]);
+    for row in &report.rows {
+        table.push(ageing_cells(row));
+    }
+    table.totals = Some(ageing_cells(&report.totals));
+
+    let basis = match report.basis {
+        AgeingBasis::InvoiceDate => "invoice date",
+        AgeingBasis::DueDate => "due date",
+    };
+    let subtitle = vec![
+        format!("As of {}, aged from {}", format_date(&report.as_of), basis),
+        format!("Total outstanding: {}", amount_in_words(report.totals.total)),
+    ];
+
+    write_tables("Receivables Ageing", &subtitle, &[table], format, Path::new(&path))
+}
+
+/// Statement of account for a client: every invoice, credit/debit note and payment in
+/// the period in date order, with the balance carried in from before the period.
+#[command]
+pub fn get_client_ledger(options: LedgerOptions) -> Result<ClientLedger, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let company_name: String = conn
+        .query_row(
+            "SELECT name FROM company WHERE id = ?1",
+            params![options.company_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("Client {} not found", options.company_id))?;
+
+    let before_period = ledger_entries(&conn, &options.company_id, "0000-00-00", &options.from_date, false)?;
+    let opening_balance: Money = before_period.iter().map(|e| e.debit - e.credit).sum();
+
+    let mut entries = ledger_entries(&conn, &options.company_id, &options.from_date, &options.to_date, true)?;
+    let mut balance = opening_balance;
+    for entry in entries.iter_mut() {
+        balance += entry.debit - entry.credit;
+        entry.balance = balance;
+    }
+
+    let total_debit: Money = entries.iter().map(|e| e.debit).sum();
+    let total_credit: Money = entries.iter().map(|e| e.credit).sum();
+
+    Ok(ClientLedger {
+        company_id: options.company_id,
+        company_name,
+        from_date: options.from_date,
+        to_date: options.to_date,
+        opening_balance,
+        entries,
+        total_debit,
+        total_credit,
+        closing_balance: balance,
+    })
+}
+
+#[command]
+pub fn export_client_ledger(options: LedgerOptions, format: ExportFormat, path: String) -> Result<Vec<String>, String> {
+    let ledger = get_client_ledger(options)?;
+
+    let mut table = Table::new("Statement of Account", &["Date", "Particulars", "Reference", "Debit", "Credit", "Balance"]);
+    table.push(vec![
+        Cell::Date(ledger.from_date.clone()),
+        "Opening Balance".into(),
+        "".into(),
+        "".into(),
+        "".into(),
+        ledger.opening_balance.into(),
+    ]);
+    for entry in &ledger.entries {
+        table.push(vec![
+            Cell::Date(entry.date.clone()),
+            entry.kind.label().into(),
+            entry.reference.as_str().into(),
+            amount_cell(entry.debit),
+            amount_cell(entry.credit),
+            entry.balance.into(),
+        ]);
+    }
+    table.totals = Some(vec![
+        Cell::Date(ledger.to_date.clone()),
+        "Closing Balance".into(),
+        "".into(),
+        ledger.total_debit.into(),
+        ledger.total_credit.into(),
+        ledger.closing_balance.into(),
+    ]);
+
+    let subtitle = vec![
+        ledger.company_name.clone(),
+        format!("For the period {} to {}", format_date(&ledger.from_date), format_date(&ledger.to_date)),
+        format!("Closing balance: {}", amount_in_words(ledger.closing_balance)),
+    ];
+
+    write_tables("Statement of Account", &subtitle, &[table], format, Path::new(&path))
+}
+
+/// Ledger entries dated from `from` up to `to` (inclusive only when `inclusive_to`), oldest first.
+/// Documents sort before payments on the same day.
+fn ledger_entries(conn: &Connection, company_id: &str, from: &str, to: &str, inclusive_to: bool) -> Result<Vec<LedgerEntry>, String> {
+    let to_op = if inclusive_to { "<=" } else { "<" };
+
+    let mut entries = Vec::new();
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT invoice_date, invoice_number, document_type, total_inr FROM invoice
+            WHERE company_id = ?1 AND status != 'cancelled' AND invoice_date >= ?2 AND invoice_date {} ?3",
+            to_op
+        ))
+        .map_err(|e| e.to_string())?;
+    let documents = stmt
+        .query_map(params![company_id, from, to], |row| {
+            let document_type: DocumentType = row.get(2)?;
+            let amount: Money = row.get(3)?;
+            let (kind, debit, credit) = match document_type {
+                DocumentType::TaxInvoice => (LedgerEntryKind::Invoice, amount, Money::ZERO),
+                DocumentType::DebitNote => (LedgerEntryKind::DebitNote, amount, Money::ZERO),
+                DocumentType::CreditNote => (LedgerEntryKind::CreditNote, Money::ZERO, amount),
+            };
+            Ok(LedgerEntry {
+                date: row.get(0)?,
+                kind,
+                reference: row.get(1)?,
+                debit,
+                credit,
+                balance: Money::ZERO,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for entry in documents {
+        entries.push(entry.map_err(|e| e.to_string())?);
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT payment_date, mode, reference, amount, tds_deducted FROM payment
+            WHERE company_id = ?1 AND payment_date >= ?2 AND payment_date {} ?3",
+            to_op
+        ))
+        .map_err(|e| e.to_string())?;
+    let payments = stmt
+        .query_map(params![company_id, from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Money>(3)?,
+                row.get::<_, Money>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    for payment in payments {
+        let (date, mode, reference, amount, tds) = payment.map_err(|e| e.to_string())?;
+        let reference = reference.unwrap_or(mode);
+        entries.push(LedgerEntry {
+            date: date.clone(),
+            kind: LedgerEntryKind::Payment,
+            reference: reference.clone(),
+            debit: Money::ZERO,
+            credit: amount,
+            balance: Money::ZERO,
+        });
+        if tds > Money::ZERO {
+            entries.push(LedgerEntry {
+                date,
+                kind: LedgerEntryKind::Tds,
+                reference,
+                debit: Money::ZERO,
+                credit: tds,
+                balance: Money::ZERO,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        let is_payment = |e: &LedgerEntry| matches!(e.kind, LedgerEntryKind::Payment | LedgerEntryKind::Tds);
+        a.date.cmp(&b.date).then(is_payment(a).cmp(&is_payment(b)))
+    });
+
+    Ok(entries)
+}
+
+/// Blank instead of 0.00 so debit and credit columns read like a ledger.
+fn amount_cell(amount: Money) -> Cell {
+    if amount.is_zero() {
+        Cell::Text(String::new())
+    } else {
+        amount.into()
+    }
+}
+
+fn ageing_cells(row: &AgeingRow) -> Vec<Cell> {
+    vec![
+        row.company_name.as_str().into(),
+        row.days_0_30.into(),
+        row.days_31_60.into(),
+        row.days_61_90.into(),
+        row.days_over_90.into(),
+        row.credit.into(),
+        row.total.into(),
+    ]
+}
+
+fn add_to_bucket(row: &mut AgeingRow, days: i64, amount: Money) {
+    match days {
+        i64::MIN..=30 => row.days_0_30 += amount,
+        31..=60 => row.days_31_60 += amount,
+        61..=90 => row.days_61_90 += amount,
+        _ => row.days_over_90 += amount,
+    }
+    row.total += amount;
+}
+
@@ -0,0 +1,17 @@
+use tauri::command;
+
+use crate::models::state::{state_for_pincode, IndianState, STATES};
+
+/// States and union territories with their GST codes, for the place of supply dropdowns.
+#[command]
+pub fn get_states() -> Vec<IndianState> {
+    STATES.to_vec()
+}
+
+/// The state a pincode belongs to, for filling in addresses. Works offline.
+#[command]
+pub fn lookup_pincode(pincode: String) -> Result<IndianState, String> {
+    state_for_pincode(&pincode)
+        .copied()
+        .ok_or_else(|| format!("No state found for pincode {}", pincode))
+}
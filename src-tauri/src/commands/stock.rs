@@ -0,0 +1,282 @@
+use chrono::{Local, Utc};
+use rusqlite::{params, params_from_iter, types::Value, Connection, Row};
+use serde::Deserialize;
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::product::fetch_item_products,
+    db::get_connection,
+    models::{
+        invoice::{DocumentType, Invoice},
+        product::ProductKind,
+        stock::{StockLedgerLine, StockLevel, StockMovement, StockMovementKind, StockMovementReport},
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct StockFilter {
+    pub product_id: Option<String>,
+    #[serde(default)]
+    pub low_stock_only: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StockReportOptions {
+    pub product_id: String,
+    pub from_date: String, // YYYY-MM-DD, inclusive
+    pub to_date: String,   // YYYY-MM-DD, inclusive
+}
+
+/// Records opening stock, a purchase or a manual adjustment. Sales and returns are
+/// only ever written by the invoices that cause them.
+#[command]
+pub fn add_stock_entry(entry: StockMovement) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    match entry.kind {
+        StockMovementKind::Opening | StockMovementKind::Purchase if entry.quantity <= 0.0 => {
+            return Err("Opening stock and purchases must add a positive quantity".to_string());
+        }
+        StockMovementKind::Adjustment if entry.quantity == 0.0 => {
+            return Err("An adjustment needs a non-zero quantity".to_string());
+        }
+        StockMovementKind::Sale | StockMovementKind::SalesReturn | StockMovementKind::Cancellation => {
+            return Err("Sales and returns are recorded through invoices".to_string());
+        }
+        _ => {}
+    }
+
+    let kind: ProductKind = conn
+        .query_row(
+            "SELECT kind FROM product WHERE id = ?1",
+            params![entry.product_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("Product {} is not in the catalog", entry.product_id))?;
+    if kind != ProductKind::Goods {
+        return Err("Stock is only tracked for goods".to_string());
+    }
+
+    // Only invoices link movements to themselves, so cancelling one never reverses a manual entry
+    let entry = StockMovement { invoice_id: None, ..entry };
+    let id = Uuid::new_v4().to_string();
+    insert_movement(&conn, &id, &entry)?;
+
+    Ok(id)
+}
+
+#[command]
+pub fn get_current_stock(filter: StockFilter) -> Result<Vec<StockLevel>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut query = "SELECT p.id, p.name, p.unit, p.low_stock_threshold,
+            COALESCE(SUM(m.quantity), 0) AS quantity
+        FROM product p
+        LEFT JOIN stock_movement m ON m.product_id = p.id
+        WHERE p.kind = 'goods' AND p.archived = 0"
+        .to_string();
+    let mut values: Vec<Value> = Vec::new();
+    if let Some(product_id) = filter.product_id {
+        values.push(product_id.into());
+        query.push_str(" AND p.id = ?1");
+    }
+    query.push_str(" GROUP BY p.id ORDER BY p.name");
+
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let quantity: f64 = row.get("quantity")?;
+            let low_stock_threshold: Option<f64> = row.get("low_stock_threshold")?;
+            Ok(StockLevel {
+                product_id: row.get("id")?,
+                name: row.get("name")?,
+                unit: row.get("unit")?,
+                quantity,
+                low_stock_threshold,
+                is_low: low_stock_threshold.is_some_and(|threshold| quantity <= threshold),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let levels = rows
+        .collect::<Result<Vec<StockLevel>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(levels
+        .into_iter()
+        .filter(|level| !filter.low_stock_only || level.is_low)
+        .collect())
+}
+
+/// Every movement of a product in the period with a running balance.
+#[command]
+pub fn get_stock_movements(options: StockReportOptions) -> Result<StockMovementReport, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let (name, unit): (String, String) = conn
+        .query_row(
+            "SELECT name, unit FROM product WHERE id = ?1",
+            params![options.product_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| format!("Product {} is not in the catalog", options.product_id))?;
+
+    let opening: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(quantity), 0) FROM stock_movement WHERE product_id = ?1 AND movement_date < ?2",
+            params![options.product_id, options.from_date],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM stock_movement
+            WHERE product_id = ?1 AND movement_date >= ?2 AND movement_date <= ?3
+            ORDER BY movement_date, created_at",
+        )
+        .map_err(|e| e.to_string())?;
+    let movements = stmt
+        .query_map(params![options.product_id, options.from_date, options.to_date], movement_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<StockMovement>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut balance = opening;
+    let mut inward = 0.0;
+    let mut outward = 0.0;
+    let mut lines = Vec::new();
+    for movement in movements {
+        balance += movement.quantity;
+        if movement.quantity > 0.0 {
+            inward += movement.quantity;
+        } else {
+            outward -= movement.quantity;
+        }
+        lines.push(StockLedgerLine { movement, balance });
+    }
+
+    Ok(StockMovementReport {
+        product_id: options.product_id,
+        name,
+        unit,
+        from_date: options.from_date,
+        to_date: options.to_date,
+        opening,
+        inward,
+        outward,
+        closing: balance,
+        lines,
+    })
+}
+
+/// Issues stock for the catalog goods on a tax invoice, or takes it back in for a credit note.
+pub(crate) fn record_invoice_stock(conn: &Connection, invoice_id: &str, invoice: &Invoice) -> Result<(), String> {
+    let (kind, sign) = match invoice.document_type {
+        DocumentType::TaxInvoice => (StockMovementKind::Sale, -1.0),
+        DocumentType::CreditNote => (StockMovementKind::SalesReturn, 1.0),
+        DocumentType::DebitNote => return Ok(()),
+    };
+
+    let products = fetch_item_products(conn, &invoice.items)?;
+    for item in &invoice.items {
+        let Some(product_id) = &item.product_id else {
+            continue;
+        };
+        if products.get(product_id).map(|p| p.kind) != Some(ProductKind::Goods) {
+            continue;
+        }
+
+        let movement = StockMovement {
+            id: None,
+            product_id: product_id.clone(),
+            movement_date: invoice.invoice_date.clone(),
+            kind,
+            quantity: sign * item.quantity,
+            reference: Some(invoice.invoice_number.clone()),
+            invoice_id: Some(invoice_id.to_string()),
+            notes: None,
+        };
+        insert_movement(conn, &Uuid::new_v4().to_string(), &movement)?;
+    }
+    Ok(())
+}
+
+/// Undoes whatever stock an invoice moved, dated today.
+pub(crate) fn reverse_invoice_stock(conn: &Connection, invoice_id: &str) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT product_id, reference, SUM(quantity) FROM stock_movement
+            WHERE invoice_id = ?1 GROUP BY product_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let moved = stmt
+        .query_map(params![invoice_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, f64>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| e.to_string())?;
+
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    for (product_id, reference, quantity) in moved {
+        if quantity == 0.0 {
+            continue;
+        }
+        let movement = StockMovement {
+            id: None,
+            product_id,
+            movement_date: today.clone(),
+            kind: StockMovementKind::Cancellation,
+            quantity: -quantity,
+            reference,
+            invoice_id: Some(invoice_id.to_string()),
+            notes: None,
+        };
+        insert_movement(conn, &Uuid::new_v4().to_string(), &movement)?;
+    }
+    Ok(())
+}
+
+fn insert_movement(conn: &Connection, id: &str, movement: &StockMovement) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO stock_movement (
+        id,
+        product_id,
+        movement_date,
+        kind,
+        quantity,
+        reference,
+        invoice_id,
+        notes,
+        created_at
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            id,
+            movement.product_id,
+            movement.movement_date,
+            movement.kind,
+            movement.quantity,
+            movement.reference,
+            movement.invoice_id,
+            movement.notes,
+            Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn movement_from_row(row: &Row) -> rusqlite::Result<StockMovement> {
+    Ok(StockMovement {
+        id: row.get("id")?,
+        product_id: row.get("product_id")?,
+        movement_date: row.get("movement_date")?,
+        kind: row.get("kind")?,
+        quantity: row.get("quantity")?,
+        reference: row.get("reference")?,
+        invoice_id: row.get("invoice_id")?,
+        notes: row.get("notes")?,
+    })
+}
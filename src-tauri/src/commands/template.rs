@@ -0,0 +1,126 @@
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension, Row};
+use tauri::command;
+use uuid::Uuid;
+
+use crate::{
+    commands::invoice::new_draft,
+    db::get_connection,
+    models::{invoice::Invoice, template::InvoiceTemplate},
+};
+
+/// Saves a new template, or replaces the one with the same id. Returns the template id.
+#[command]
+pub fn save_invoice_template(template: InvoiceTemplate) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let name = template.name.trim();
+    if name.is_empty() {
+        return Err("Template name is required".to_string());
+    }
+    if template.invoice.items.is_empty() {
+        return Err("A template needs at least one item".to_string());
+    }
+
+    let duplicate: Option<String> = conn
+        .query_row(
+            "SELECT id FROM invoice_template WHERE company_id = ?1 AND name = ?2",
+            params![template.company_id, name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if duplicate.is_some() && duplicate != template.id {
+        return Err(format!("This client already has a template named {}", name));
+    }
+
+    let invoice_json = serde_json::to_string(&Invoice {
+        company_id: Some(template.company_id.clone()),
+        ..template.invoice
+    })
+    .map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    match &template.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE invoice_template SET name = ?1, company_id = ?2, invoice_json = ?3, updated_at = ?4 WHERE id = ?5",
+                    params![name, template.company_id, invoice_json, now, id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Template {} not found", id));
+            }
+            Ok(id.clone())
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO invoice_template (id, name, company_id, invoice_json, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![id, name, template.company_id, invoice_json, now],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+    }
+}
+
+#[command]
+pub fn delete_invoice_template(id: String) -> Result<String, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let deleted = conn
+        .execute("DELETE FROM invoice_template WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    if deleted == 0 {
+        return Err(format!("Template {} not found", id));
+    }
+    Ok("Template deleted.".to_string())
+}
+
+#[command]
+pub fn get_invoice_templates(company_id: Option<String>) -> Result<Vec<InvoiceTemplate>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM invoice_template WHERE ?1 IS NULL OR company_id = ?1 ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![company_id], template_from_row)
+        .map_err(|e| e.to_string())?;
+
+    let templates: Result<Vec<InvoiceTemplate>, rusqlite::Error> = rows.collect();
+    templates.map_err(|e| e.to_string())
+}
+
+/// A new draft invoice pre-filled from the template, numbered and dated like a duplicate.
+#[command]
+pub fn invoice_from_template(id: String) -> Result<Invoice, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let template = conn
+        .query_row("SELECT * FROM invoice_template WHERE id = ?1", params![id], template_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Template {} not found", id))?;
+
+    new_draft(&conn, &template.invoice)
+}
+
+fn template_from_row(row: &Row) -> rusqlite::Result<InvoiceTemplate> {
+    let invoice_json: String = row.get("invoice_json")?;
+    let invoice = serde_json::from_str(&invoice_json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+
+    Ok(InvoiceTemplate {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        company_id: row.get("company_id")?,
+        invoice,
+    })
+}
@@ -0,0 +1,90 @@
+use rusqlite::Connection;
+use tauri::command;
+
+use crate::{
+    commands::{invoice::fetch_invoices, payment::invoice_balance, user::load_upi_payee},
+    db::get_connection,
+    models::{invoice::{DocumentType, Invoice, InvoiceStatus}, money::Money},
+    utils::upi::{qr_png, qr_svg, upi_uri, QrFormat},
+};
+
+/// The invoice's UPI payment QR as PNG or SVG bytes, for the print view. It asks for
+/// what is still outstanding, so a part paid invoice only collects the balance.
+#[command]
+pub fn get_invoice_upi_qr(invoice_id: String, format: QrFormat) -> Result<Vec<u8>, String> {
+    let conn = get_connection().map_err(|e| e.to_string())?;
+
+    let invoice = fetch_invoices(&conn, "SELECT * FROM invoice WHERE id = ?1", &[invoice_id.clone().into()])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+    if load_upi_payee(&conn)?.is_none() {
+        return Err("Add a UPI ID to the profile to show a payment QR".to_string());
+    }
+
+    let outstanding = invoice_balance(&conn, &invoice_id)?.outstanding;
+    let uri = invoice_upi_uri(&conn, &invoice, outstanding)?
+        .ok_or_else(|| format!("{} cannot be paid by UPI", invoice.invoice_number))?;
+    match format {
+        QrFormat::Png => qr_png(&uri),
+        QrFormat::Svg => qr_svg(&uri).map(String::into_bytes),
+    }
+}
+
+/// `upi://pay` link for `amount` (in rupees) with the invoice number as the note. None when
+/// the profile has no UPI ID or nothing is payable in rupees: credit notes, cancelled
+/// invoices, foreign currency invoices and nothing outstanding.
+pub(crate) fn invoice_upi_uri(conn: &Connection, invoice: &Invoice, amount: Money) -> Result<Option<String>, String> {
+    let Some((upi_id, payee_name)) = load_upi_payee(conn)? else {
+        return Ok(None);
+    };
+    if invoice.document_type == DocumentType::CreditNote
+        || invoice.status == InvoiceStatus::Cancelled
+        || invoice.foreign_currency.is_some()
+        || amount.paise() <= 0
+    {
+        return Ok(None);
+    }
+
+    let note = format!("{} {}", invoice.document_type.label(), invoice.invoice_number);
+    Ok(Some(upi_uri(&upi_id, &payee_name, amount, &note)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::run_schema_files, models::invoice::ForeignCurrency};
+
+    #[test]
+    fn only_rupee_invoices_with_a_balance_get_a_upi_link() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_schema_files(&conn, &["src/db/schema/profile.sql"]).unwrap();
+        let invoice = Invoice { invoice_number: "INV-1".to_string(), ..Default::default() };
+        let amount = Money::from_rupees(118.0);
+        assert_eq!(invoice_upi_uri(&conn, &invoice, amount).unwrap(), None); // No profile yet
+
+        conn.execute_batch(
+            "INSERT INTO profile VALUES ('profile', 'Me', '', '', '', '', '', '', '', '', '', '', '', '', 'INV', 1, 1, 1,
+                'per_invoice', 'nearest_rupee', 'me@okhdfcbank', NULL)",
+        )
+        .unwrap();
+        assert!(invoice_upi_uri(&conn, &invoice, amount).unwrap().unwrap().starts_with("upi://pay?"));
+
+        let cases = [
+            (Invoice { document_type: DocumentType::CreditNote, ..invoice.clone() }, amount),
+            (Invoice { status: InvoiceStatus::Cancelled, ..invoice.clone() }, amount),
+            (
+                Invoice {
+                    foreign_currency: Some(ForeignCurrency { code: "USD".to_string(), exchange_rate: 83.0 }),
+                    ..invoice.clone()
+                },
+                amount,
+            ),
+            (invoice.clone(), Money::ZERO),
+            (invoice.clone(), Money::from_rupees(-10.0)),
+        ];
+        for (invoice, amount) in cases {
+            assert_eq!(invoice_upi_uri(&conn, &invoice, amount).unwrap(), None, "{:?} {}", invoice.document_type, amount);
+        }
+    }
+}
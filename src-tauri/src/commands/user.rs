@@ -8,9 +8,10 @@ use std::fs;
 use std::{fs::File, io, path::PathBuf};
 use uuid::Uuid;
 
+use crate::commands::{recurring::run_due_recurring_invoices, reminder::spawn_due_payment_reminders};
 use crate::db::{get_connection, init_db};
-use crate::models::user::Profile;
-use crate::utils::{get_current_user_db_path, get_current_user_hash, hash_email};
+use crate::models::{address::Address, bank::validate_bank_details, invoice::{BankDetails, RoundingRules}, state::find_state, user::Profile};
+use crate::utils::{get_current_user_db_path, get_current_user_hash, hash_email, upi::is_upi_id};
 use crate::{models::user::UserSession, utils::get_app_data_path};
 
 #[tauri::command]
@@ -48,7 +49,7 @@ pub fn signup_user(email: String, password: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn login(email: String, password: String) -> Result<String, String> {
+pub fn login(app: tauri::AppHandle, email: String, password: String) -> Result<String, String> {
     let app_data_path = get_app_data_path()?;
     let db_path = app_data_path.join("app_data.db");
     let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
@@ -77,6 +78,10 @@ pub fn login(email: String, password: String) -> Result<String, String> {
             std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
         init_db().expect("Failed to initialize the database");
+        if let Err(e) = run_due_recurring_invoices() {
+            log::warn!("Recurring invoices were not generated: {}", e);
+        }
+        spawn_due_payment_reminders(app);
         Ok(format!("User Logged In!"))
     } else {
         Err("Invalid email or password.".to_string())
@@ -158,34 +163,29 @@ pub fn complete_onboarding(profile_info: Profile) -> Result<String, String> {
 
     let current_user_session = load_session()?;
 
+    let upi_id = profile_info.upi_id.as_deref().map(str::trim).filter(|upi_id| !upi_id.is_empty());
+    if let Some(upi_id) = upi_id {
+        if !is_upi_id(upi_id) {
+            return Err(format!("{} is not a valid UPI ID", upi_id));
+        }
+    }
+    if !profile_info.bank_account_number.trim().is_empty() {
+        validate_bank_details(&BankDetails {
+            bank_name: profile_info.bank_name.clone(),
+            branch: profile_info.bank_branch.clone(),
+            account_name: profile_info.bank_account_name.clone(),
+            account_no: profile_info.bank_account_number.clone(),
+            ifsc_code: profile_info.bank_ifsc.trim().to_uppercase(),
+        })?;
+    }
+
     // 2. Open user-specific DB
     let user_conn = get_connection().map_err(|e| e.to_string())?;
 
-    user_conn
-        .execute(
-            "CREATE TABLE IF NOT EXISTS profile (
-                id TEXT PRIMARY KEY,
-                company_name TEXT NOT NULL,
-                gst_number TEXT,
-                address TEXT,
-                city TEXT,
-                state TEXT,
-                pincode TEXT,
-                phone TEXT,
-                email TEXT,
-                bank_name TEXT,
-                bank_branch TEXT,
-                bank_ifsc TEXT,
-                bank_account_name TEXT,
-                bank_account_number TEXT,
-                invoice_prefix TEXT,
-                next_invoice_number INTEGER NOT NULL,
-                next_debit_number INTEGER NOT NULL,
-                next_credit_number INTEGER NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-            [],
-        )
+    crate::db::run_schema_files(&user_conn, &["src/db/schema/profile.sql"]).map_err(|e| e.to_string())?;
+
+    let first_profile: bool = user_conn
+        .query_row("SELECT NOT EXISTS (SELECT 1 FROM profile)", [], |row| row.get(0))
         .map_err(|e| e.to_string())?;
 
     // Insert or update profile row
@@ -211,9 +211,12 @@ pub fn complete_onboarding(profile_info: Profile) -> Result<String, String> {
             next_invoice_number, 
             next_debit_number, 
             next_credit_number, 
+            tax_rounding,
+            round_off,
+            upi_id,
             updated_at
             )
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)
              ON CONFLICT(id) DO UPDATE SET
                 company_name = excluded.company_name,
                 gst_number = excluded.gst_number,
@@ -232,6 +235,9 @@ pub fn complete_onboarding(profile_info: Profile) -> Result<String, String> {
                 next_invoice_number = excluded.next_invoice_number,
                 next_debit_number = excluded.next_debit_number,
                 next_credit_number = excluded.next_credit_number,
+                tax_rounding = excluded.tax_rounding,
+                round_off = excluded.round_off,
+                upi_id = excluded.upi_id,
                 updated_at = excluded.updated_at",
             params![
                 "profile", // static ID since only one profile per user
@@ -252,10 +258,16 @@ pub fn complete_onboarding(profile_info: Profile) -> Result<String, String> {
                 profile_info.next_invoice_number,
                 profile_info.next_debit_number,
                 profile_info.next_credit_number,
+                profile_info.tax_rounding,
+                profile_info.round_off,
+                upi_id,
                 now,
             ],
         )
         .map_err(|e| e.to_string())?;
+    if first_profile {
+        crate::db::backfill_bank_accounts(&user_conn).map_err(|e| e.to_string())?;
+    }
 
     // Update onboarding flag in global auth DB
     global_conn
@@ -276,13 +288,16 @@ pub fn complete_onboarding(profile_info: Profile) -> Result<String, String> {
 #[tauri::command]
 pub fn get_profile_details() -> Result<Profile, String> {
     let user_conn = get_connection().map_err(|e| e.to_string())?;
+    load_profile(&user_conn)
+}
 
+pub(crate) fn load_profile(user_conn: &Connection) -> Result<Profile, String> {
     let profile_details: Profile = user_conn
             .query_row(
             r#"
             SELECT company_name, gst_number, phone, email, address, city, state, pincode,
                 bank_name, bank_branch, bank_ifsc, bank_account_name, bank_account_number, invoice_prefix,
-                next_invoice_number, next_debit_number, next_credit_number
+                next_invoice_number, next_debit_number, next_credit_number, tax_rounding, round_off, upi_id
             FROM profile LIMIT 1
             "#,
             [],
@@ -305,6 +320,9 @@ pub fn get_profile_details() -> Result<Profile, String> {
                     next_invoice_number: row.get(14)?,
                     next_debit_number: row.get(15)?,
                     next_credit_number: row.get(16)?,
+                    tax_rounding: row.get(17)?,
+                    round_off: row.get(18)?,
+                    upi_id: row.get(19)?,
                 })
             },
         )
@@ -313,6 +331,63 @@ pub fn get_profile_details() -> Result<Profile, String> {
     Ok(profile_details)
 }
 
+/// The business address for new invoices, with the state as a GST code (from the
+/// pincode if the profile's state isn't one we know). None until onboarding has
+/// saved a profile.
+pub(crate) fn load_profile_address(user_conn: &Connection) -> Result<Option<Address>, String> {
+    user_conn
+        .query_row("SELECT address, city, state, pincode FROM profile LIMIT 1", [], |row| {
+            let state: String = row.get(2)?;
+            Ok(Some(Address {
+                line1: row.get(0)?,
+                city: row.get(1)?,
+                state_code: find_state(&state).map_or(String::new(), |state| state.code.to_string()),
+                pincode: row.get(3)?,
+                ..Default::default()
+            }
+            .normalized()))
+        })
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            rusqlite::Error::SqliteFailure(_, Some(message)) if message.starts_with("no such table") => Ok(None),
+            e => Err(e.to_string()),
+        })
+}
+
+/// The UPI ID payments go to and the business name shown to the payer, None until one
+/// is saved on the profile.
+pub(crate) fn load_upi_payee(user_conn: &Connection) -> Result<Option<(String, String)>, String> {
+    user_conn
+        .query_row("SELECT upi_id, company_name FROM profile LIMIT 1", [], |row| {
+            let upi_id: Option<String> = row.get(0)?;
+            let company_name: String = row.get(1)?;
+            Ok(upi_id.map(|upi_id| (upi_id, company_name)))
+        })
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            rusqlite::Error::SqliteFailure(_, Some(message)) if message.starts_with("no such table") => Ok(None),
+            e => Err(e.to_string()),
+        })
+}
+
+/// The profile's rounding rules, or the defaults before onboarding has created a profile.
+pub(crate) fn load_rounding_rules(user_conn: &Connection) -> Result<RoundingRules, String> {
+    user_conn
+        .query_row("SELECT tax_rounding, round_off FROM profile LIMIT 1", [], |row| {
+            Ok(RoundingRules {
+                tax_rounding: row.get(0)?,
+                round_off: row.get(1)?,
+            })
+        })
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(RoundingRules::default()),
+            rusqlite::Error::SqliteFailure(_, Some(message)) if message.starts_with("no such table") => {
+                Ok(RoundingRules::default())
+            }
+            e => Err(e.to_string()),
+        })
+}
+
 // Want to implement Google OAuth2 authentication flow with data backup in future, below code is not in use anywhere yet
 
 #[tauri::command]
@@ -1,10 +1,15 @@
-use rusqlite::{Connection, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use tauri::command;
+use uuid::Uuid;
 
-use crate::utils::{get_app_data_path, get_current_user_db_path};
+use crate::{
+    models::{address::Address, bank::validate_bank_details, invoice::{BankDetails, TransportDetails}, state::find_state},
+    utils::{get_app_data_path, get_current_user_db_path},
+};
 
 
-fn run_schema_files(conn: &Connection, files: &[&str]) -> Result<()> {
+pub(crate) fn run_schema_files(conn: &Connection, files: &[&str]) -> Result<()> {
     for file in files {
         let sql = std::fs::read_to_string(file).map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
         conn.execute_batch(&sql)?;
@@ -12,6 +17,257 @@ fn run_schema_files(conn: &Connection, files: &[&str]) -> Result<()> {
     Ok(())
 }
 
+/// Adds a column to a table created by an older schema file. `CREATE TABLE IF NOT EXISTS`
+/// never touches existing tables, so new columns need this on databases created earlier.
+/// Tables that don't exist yet are skipped; they get the column when they are created.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let columns = table_columns(conn, table)?;
+
+    if !columns.is_empty() && !columns.iter().any(|(name, _)| name == column) {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+/// Name and declared type of every column in `table`, empty if the table doesn't exist.
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| Ok((row.get("name")?, row.get("type")?)))?
+        .collect::<Result<Vec<(String, String)>>>()?;
+    Ok(columns)
+}
+
+/// Rebuilds a table whose amounts were stored as REAL rupees (and rates as REAL percent)
+/// so they hold INTEGER paise (and hundredths of a percent), using the current schema file.
+/// SQLite can't change a column's type in place, so the rows are copied into a new table.
+fn convert_to_fixed_point(conn: &Connection, table: &str, schema_file: &str, columns_to_convert: &[&str]) -> Result<()> {
+    let columns = table_columns(conn, table)?;
+    let needs_conversion = columns
+        .iter()
+        .any(|(name, kind)| columns_to_convert.contains(&name.as_str()) && kind.eq_ignore_ascii_case("REAL"));
+    if !needs_conversion {
+        return Ok(());
+    }
+
+    let old_table = format!("{}_real", table);
+    // Legacy mode (with foreign keys off, see run_migrations) keeps other tables'
+    // foreign keys pointing at `table` rather than at the old copy
+    conn.execute_batch(&format!(
+        "PRAGMA legacy_alter_table = ON;
+        ALTER TABLE {} RENAME TO {};
+        PRAGMA legacy_alter_table = OFF;",
+        table, old_table
+    ))?;
+
+    // Indexes move with the renamed table, drop them so the schema file can recreate them
+    let indexes = {
+        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL")?;
+        let names = stmt
+            .query_map([&old_table], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>>>()?;
+        names
+    };
+    for index in indexes {
+        conn.execute_batch(&format!("DROP INDEX {}", index))?;
+    }
+
+    run_schema_files(conn, &[schema_file])?;
+
+    let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+    let values: Vec<String> = names
+        .iter()
+        .map(|name| {
+            if columns_to_convert.contains(name) {
+                format!("CAST(ROUND({} * 100) AS INTEGER)", name)
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+    conn.execute_batch(&format!(
+        "INSERT INTO {} ({}) SELECT {} FROM {};
+        DROP TABLE {};",
+        table,
+        names.join(", "),
+        values.join(", "),
+        old_table,
+        old_table
+    ))?;
+
+    Ok(())
+}
+
+/// Brings databases created by older versions up to the current schema.
+fn run_migrations(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "invoice", "company_id", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "status", "TEXT NOT NULL DEFAULT 'unpaid'")?;
+    add_column_if_missing(conn, "company", "payment_terms_days", "INTEGER")?;
+    add_column_if_missing(conn, "invoice", "document_type", "TEXT NOT NULL DEFAULT 'tax_invoice'")?;
+    add_column_if_missing(conn, "invoice", "original_invoice_id", "TEXT")?;
+    add_column_if_missing(conn, "product", "low_stock_threshold", "REAL")?;
+    add_column_if_missing(conn, "invoice", "round_off", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "invoice", "discount_json", "TEXT")?;
+    add_column_if_missing(conn, "quotation", "discount_json", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "tcs_percentage", "INTEGER")?;
+    add_column_if_missing(conn, "invoice", "tcs_amount", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "invoice", "tds_percentage", "INTEGER")?;
+    add_column_if_missing(conn, "invoice", "tds_expected", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "invoice", "supply_type", "TEXT NOT NULL DEFAULT 'regular'")?;
+    add_column_if_missing(conn, "invoice", "lut_reference", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "shipping_bill_json", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "currency", "TEXT NOT NULL DEFAULT 'INR'")?;
+    add_column_if_missing(conn, "invoice", "exchange_rate", "REAL NOT NULL DEFAULT 1")?;
+    add_column_if_missing(conn, "invoice", "total_inr", "INTEGER")?;
+    add_column_if_missing(conn, "invoice", "place_of_supply", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "issuer_address_json", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "recipient_address_json", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "bill_to_address_id", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "ship_to_json", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "ship_to_address_id", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "bank_account_id", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "tax_rounding", "TEXT")?;
+    add_column_if_missing(conn, "invoice", "round_off_rule", "TEXT")?;
+    add_column_if_missing(conn, "company", "address_line1", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "company", "address_line2", "TEXT")?;
+    add_column_if_missing(conn, "company", "city", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "company", "district", "TEXT")?;
+    add_column_if_missing(conn, "company", "state_code", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "profile", "tax_rounding", "TEXT NOT NULL DEFAULT 'per_invoice'")?;
+    add_column_if_missing(conn, "profile", "round_off", "TEXT NOT NULL DEFAULT 'nearest_rupee'")?;
+    add_column_if_missing(conn, "profile", "upi_id", "TEXT")?;
+
+    // Older invoices only carry the recipient's GSTIN, link them to the matching client
+    conn.execute_batch(
+        "UPDATE invoice SET company_id = (
+            SELECT company.id FROM company WHERE company.gst_number = invoice.recipient_gst_number LIMIT 1
+        ) WHERE company_id IS NULL",
+    )?;
+
+    // Amounts used to be REAL rupees and rates REAL percentages. Foreign keys can only be
+    // switched off outside a transaction.
+    conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+    let tx = conn.unchecked_transaction()?;
+    convert_to_fixed_point(&tx, "invoice", "src/db/schema/invoice.sql", &[
+        "amount", "cgst_percentage", "sgst_percentage", "igst_percentage", "total_amount",
+    ])?;
+    convert_to_fixed_point(&tx, "quotation", "src/db/schema/quotation.sql", &[
+        "amount", "cgst_percentage", "sgst_percentage", "igst_percentage", "total_amount",
+    ])?;
+    convert_to_fixed_point(&tx, "payment", "src/db/schema/payment.sql", &["amount", "tds_deducted"])?;
+    convert_to_fixed_point(&tx, "payment_allocation", "src/db/schema/payment.sql", &["amount", "tds_amount"])?;
+    convert_to_fixed_point(&tx, "product", "src/db/schema/product.sql", &["default_rate", "gst_rate"])?;
+    tx.commit()?;
+    conn.execute_batch("PRAGMA foreign_keys = ON")?;
+
+    // Invoices from before foreign currency billing were all in rupees
+    conn.execute_batch("UPDATE invoice SET total_inr = total_amount WHERE total_inr IS NULL")?;
+
+    backfill_place_of_supply(conn)?;
+    backfill_rounding_rules(conn)?;
+    backfill_company_addresses(conn)?;
+
+    Ok(())
+}
+
+/// Bank details used to live on the profile alone. Valid ones become the first account,
+/// which is the default. Run once, when the bank_account table is created or the profile
+/// is first saved, so deleting every account doesn't bring the profile's back.
+pub(crate) fn backfill_bank_accounts(conn: &Connection) -> Result<()> {
+    if table_columns(conn, "profile")?.is_empty() {
+        return Ok(());
+    }
+    let details = conn
+        .query_row(
+            "SELECT bank_name, bank_branch, bank_account_name, bank_account_number, bank_ifsc FROM profile LIMIT 1",
+            [],
+            |row| {
+                Ok(BankDetails {
+                    bank_name: row.get::<_, Option<String>>(0)?.unwrap_or_default().trim().to_string(),
+                    branch: row.get::<_, Option<String>>(1)?.unwrap_or_default().trim().to_string(),
+                    account_name: row.get::<_, Option<String>>(2)?.unwrap_or_default().trim().to_string(),
+                    account_no: row.get::<_, Option<String>>(3)?.unwrap_or_default().trim().to_string(),
+                    ifsc_code: row.get::<_, Option<String>>(4)?.unwrap_or_default().trim().to_ascii_uppercase(),
+                })
+            },
+        )
+        .optional()?;
+    let Some(details) = details.filter(|details| validate_bank_details(details).is_ok()) else {
+        return Ok(());
+    };
+
+    conn.execute(
+        "INSERT INTO bank_account (id, label, bank_name, branch, account_name, account_no, ifsc_code, is_default, created_at)
+        SELECT ?1, ?2, ?2, ?3, ?4, ?5, ?6, 1, ?7
+        WHERE NOT EXISTS (SELECT 1 FROM bank_account)",
+        params![
+            Uuid::new_v4().to_string(), details.bank_name, details.branch, details.account_name, details.account_no,
+            details.ifsc_code, Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Invoices saved before they kept their rounding rules were worked out under the profile's.
+fn backfill_rounding_rules(conn: &Connection) -> Result<()> {
+    if table_columns(conn, "profile")?.is_empty() {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "UPDATE invoice SET
+            tax_rounding = (SELECT tax_rounding FROM profile LIMIT 1),
+            round_off_rule = (SELECT round_off FROM profile LIMIT 1)
+        WHERE tax_rounding IS NULL",
+    )
+}
+
+/// Client addresses used to be one block of text, which is kept whole as the first line.
+/// The state comes from the pincode, or the GSTIN when the pincode isn't recognised.
+fn backfill_company_addresses(conn: &Connection) -> Result<()> {
+    conn.execute_batch("UPDATE company SET address_line1 = address WHERE address_line1 = ''")?;
+
+    let pending = {
+        let mut stmt = conn.prepare("SELECT id, pincode, gst_number FROM company WHERE state_code = ''")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+            .collect::<Result<Vec<(String, String, String)>>>()?;
+        rows
+    };
+    for (id, pincode, gst_number) in pending {
+        let state_code = Address::from_legacy("", &pincode, &gst_number).state_code;
+        if !state_code.is_empty() {
+            conn.execute("UPDATE company SET state_code = ?1 WHERE id = ?2", params![state_code, id])?;
+        }
+    }
+    Ok(())
+}
+
+/// Invoices saved before the place of supply had a column of its own kept it in their
+/// transport details, as a state code or name. Ones the state master doesn't know stay empty.
+fn backfill_place_of_supply(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "UPDATE invoice SET place_of_supply = '96'
+        WHERE place_of_supply IS NULL AND supply_type IN ('export_with_payment', 'export_without_payment')",
+    )?;
+
+    let pending = {
+        let mut stmt = conn.prepare("SELECT id, transport_details_json FROM invoice WHERE place_of_supply IS NULL")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<(String, String)>>>()?;
+        rows
+    };
+    for (id, transport_details_json) in pending {
+        let state = serde_json::from_str::<TransportDetails>(&transport_details_json)
+            .ok()
+            .and_then(|transport| find_state(&transport.place_of_supply));
+        if let Some(state) = state {
+            conn.execute("UPDATE invoice SET place_of_supply = ?1 WHERE id = ?2", params![state.code, id])?;
+        }
+    }
+    Ok(())
+}
+
 pub fn get_connection() -> Result<Connection, String> {
     let db_path = get_current_user_db_path()?;
     Connection::open(db_path).map_err(|e| e.to_string())
@@ -36,11 +292,93 @@ pub fn init_db() -> Result<String, String> {
     let db_path = get_current_user_db_path()?;
 
     let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    let new_bank_accounts = table_columns(&conn, "bank_account").map_err(|e| e.to_string())?.is_empty();
 
     run_schema_files(&conn, &[
         "src/db/schema/invoice.sql",
         "src/db/schema/company.sql",
+        "src/db/schema/payment.sql",
+        "src/db/schema/product.sql",
+        "src/db/schema/stock.sql",
+        "src/db/schema/quotation.sql",
+        "src/db/schema/challan.sql",
+        "src/db/schema/recurring.sql",
+        "src/db/schema/template.sql",
+        "src/db/schema/email.sql",
+        "src/db/schema/reminder.sql",
+        "src/db/schema/bank.sql",
     ]).map_err(|e| e.to_string())?;
 
+    run_migrations(&conn).map_err(|e| e.to_string())?;
+    if new_bank_accounts {
+        backfill_bank_accounts(&conn).map_err(|e| e.to_string())?;
+    }
+
     Ok("Database initialized successfully".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_real_rupees_to_paise() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE product (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                hsn_code TEXT NOT NULL,
+                unit TEXT NOT NULL,
+                default_rate REAL NOT NULL,
+                gst_rate REAL NOT NULL,
+                kind TEXT NOT NULL,
+                archived BOOLEAN NOT NULL DEFAULT 0,
+                low_stock_threshold REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE INDEX idx_product_name ON product (name);
+            INSERT INTO product VALUES ('p1', 'Bolt', NULL, '7318', 'pcs', 12.345, 18.0, 'goods', 0, 2.5, 'now', 'now');
+            INSERT INTO product VALUES ('p2', 'Repair', 'On site', '9987', 'hrs', 0.25, 0.125, 'services', 1, NULL, 'now', 'now');",
+        )
+        .unwrap();
+
+        convert_to_fixed_point(&conn, "product", "src/db/schema/product.sql", &["default_rate", "gst_rate"]).unwrap();
+
+        let columns = table_columns(&conn, "product").unwrap();
+        assert!(columns.contains(&("default_rate".to_string(), "INTEGER".to_string())));
+        assert!(columns.contains(&("gst_rate".to_string(), "INTEGER".to_string())));
+
+        let rows = conn
+            .prepare("SELECT id, description, default_rate, gst_rate, archived, low_stock_threshold FROM product ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, bool>(4)?,
+                    row.get::<_, Option<f64>>(5)?,
+                ))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows, vec![
+            ("p1".to_string(), None, 1235, 1800, false, Some(2.5)),
+            ("p2".to_string(), Some("On site".to_string()), 25, 13, true, None),
+        ]);
+
+        // The old copy is gone, the index is back and a second run leaves the rows alone
+        let tables: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name IN ('product_real', 'idx_product_name')", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 1);
+        convert_to_fixed_point(&conn, "product", "src/db/schema/product.sql", &["default_rate", "gst_rate"]).unwrap();
+        let rate: i64 = conn.query_row("SELECT default_rate FROM product WHERE id = 'p1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(rate, 1235);
+    }
+}
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS bank_account (
+    id TEXT PRIMARY KEY NOT NULL,
+    label TEXT NOT NULL,
+    bank_name TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    account_name TEXT NOT NULL,
+    account_no TEXT NOT NULL,
+    ifsc_code TEXT NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT 0, -- exactly one account once there are any
+    created_at TEXT NOT NULL
+);
@@ -0,0 +1,33 @@
+CREATE TABLE IF NOT EXISTS delivery_challan (
+    id TEXT PRIMARY KEY NOT NULL,
+    challan_number TEXT NOT NULL UNIQUE,
+    challan_date TEXT NOT NULL,
+    purpose TEXT NOT NULL DEFAULT 'on_approval',
+    status TEXT NOT NULL DEFAULT 'open',
+    company_id TEXT,
+    issuer_name TEXT NOT NULL,
+    issuer_address TEXT NOT NULL,
+    issuer_gst_number TEXT NOT NULL,
+    issuer_phone TEXT NOT NULL,
+    recipient_name TEXT NOT NULL,
+    recipient_address TEXT NOT NULL,
+    recipient_gst_number TEXT NOT NULL,
+    recipient_phone TEXT NOT NULL,
+    transport_details_json TEXT NOT NULL,
+    items_json TEXT NOT NULL,
+    notes TEXT,
+    invoice_id TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_delivery_challan_company ON delivery_challan (company_id);
+CREATE INDEX IF NOT EXISTS idx_delivery_challan_invoice ON delivery_challan (invoice_id);
+
+-- Numbering for documents that keep their own series outside the profile counters
+CREATE TABLE IF NOT EXISTS document_series (
+    document TEXT PRIMARY KEY NOT NULL,
+    prefix TEXT NOT NULL,
+    next_number INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO document_series (document, prefix, next_number) VALUES ('delivery_challan', 'DC', 1);
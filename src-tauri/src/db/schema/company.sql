@@ -6,5 +6,40 @@ CREATE TABLE IF NOT EXISTS company (
     pincode TEXT NOT NULL,
     phone TEXT NOT NULL, 
     gst_number TEXT NOT NULL,
-    email TEXT
-);
\ No newline at end of file
+    email TEXT,
+    payment_terms_days INTEGER,
+    address_line1 TEXT NOT NULL DEFAULT '', -- address is these parts on one line
+    address_line2 TEXT,
+    city TEXT NOT NULL DEFAULT '',
+    district TEXT,
+    state_code TEXT NOT NULL DEFAULT '' -- GST state code
+);
+CREATE TABLE IF NOT EXISTS company_address (
+    id TEXT PRIMARY KEY NOT NULL,
+    company_id TEXT NOT NULL REFERENCES company(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    address_line1 TEXT NOT NULL,
+    address_line2 TEXT,
+    city TEXT NOT NULL,
+    district TEXT,
+    state_code TEXT NOT NULL, -- GST state code
+    pincode TEXT NOT NULL,
+    gst_number TEXT,
+    kind TEXT NOT NULL DEFAULT 'both', -- billing, shipping or both
+    is_default BOOLEAN NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_company_address_company ON company_address (company_id);
+
+CREATE TABLE IF NOT EXISTS company_contact (
+    id TEXT PRIMARY KEY NOT NULL,
+    company_id TEXT NOT NULL REFERENCES company(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    role TEXT,
+    phone TEXT,
+    email TEXT,
+    receives_invoices BOOLEAN NOT NULL DEFAULT 0,
+    receives_reminders BOOLEAN NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_company_contact_company ON company_contact (company_id);
@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS email_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1), -- a single row
+    host TEXT NOT NULL,
+    port INTEGER NOT NULL,
+    security TEXT NOT NULL DEFAULT 'starttls', -- none, starttls or tls
+    username TEXT,
+    password TEXT, -- plain text, see save_email_settings
+    from_name TEXT NOT NULL,
+    from_email TEXT NOT NULL,
+    subject_template TEXT,
+    body_template TEXT,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS email_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    invoice_id TEXT NOT NULL,
+    recipients TEXT NOT NULL, -- comma separated
+    subject TEXT NOT NULL,
+    status TEXT NOT NULL, -- sent or failed
+    error TEXT,
+    sent_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_log_invoice ON email_log (invoice_id);
@@ -16,10 +16,35 @@ CREATE TABLE IF NOT EXISTS invoice (
     transport_details_json TEXT NOT NULL,
     reverse_charge BOOLEAN NOT NULL,
     items_json TEXT NOT NULL,
-    amount REAL NOT NULL,
-    cgst_percentage REAL,
-    sgst_percentage REAL,
-    igst_percentage REAL,
+    amount INTEGER NOT NULL, -- paise
+    cgst_percentage INTEGER, -- hundredths of a percent
+    sgst_percentage INTEGER,
+    igst_percentage INTEGER,
     additional_charges_json TEXT,
-    total_amount REAL NOT NULL
+    total_amount INTEGER NOT NULL,
+    company_id TEXT,
+    status TEXT NOT NULL DEFAULT 'unpaid',
+    document_type TEXT NOT NULL DEFAULT 'tax_invoice',
+    original_invoice_id TEXT,
+    round_off INTEGER NOT NULL DEFAULT 0, -- paise
+    discount_json TEXT,
+    tcs_percentage INTEGER, -- hundredths of a percent
+    tcs_amount INTEGER NOT NULL DEFAULT 0, -- paise
+    tds_percentage INTEGER,
+    tds_expected INTEGER NOT NULL DEFAULT 0,
+    supply_type TEXT NOT NULL DEFAULT 'regular',
+    lut_reference TEXT,
+    shipping_bill_json TEXT,
+    currency TEXT NOT NULL DEFAULT 'INR', -- amounts above are in this currency
+    exchange_rate REAL NOT NULL DEFAULT 1, -- rupees per unit of currency
+    total_inr INTEGER, -- paise
+    place_of_supply TEXT, -- GST state code, 96 for exports
+    issuer_address_json TEXT, -- the addresses above in parts, empty on older invoices
+    recipient_address_json TEXT,
+    bill_to_address_id TEXT, -- company_address entries the addresses were copied from
+    ship_to_json TEXT, -- empty when shipped to the bill-to address
+    ship_to_address_id TEXT,
+    bank_account_id TEXT, -- bank_account the bank details were copied from
+    tax_rounding TEXT, -- profile rounding rules the totals were worked out under
+    round_off_rule TEXT
 );
\ No newline at end of file
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS payment (
+    id TEXT PRIMARY KEY NOT NULL,
+    company_id TEXT,
+    payment_date TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- paise
+    mode TEXT NOT NULL,
+    reference TEXT,
+    tds_deducted INTEGER NOT NULL DEFAULT 0,
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS payment_allocation (
+    id TEXT PRIMARY KEY NOT NULL,
+    payment_id TEXT NOT NULL REFERENCES payment(id) ON DELETE CASCADE,
+    invoice_id TEXT NOT NULL REFERENCES invoice(id),
+    amount INTEGER NOT NULL, -- paise
+    tds_amount INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_allocation_invoice ON payment_allocation (invoice_id);
+CREATE INDEX IF NOT EXISTS idx_payment_allocation_payment ON payment_allocation (payment_id);
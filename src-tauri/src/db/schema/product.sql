@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS product (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    hsn_code TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    default_rate INTEGER NOT NULL, -- paise
+    gst_rate INTEGER NOT NULL, -- hundredths of a percent
+    kind TEXT NOT NULL,
+    archived BOOLEAN NOT NULL DEFAULT 0,
+    low_stock_threshold REAL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_name ON product (name);
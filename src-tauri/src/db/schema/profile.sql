@@ -17,5 +17,8 @@ CREATE TABLE IF NOT EXISTS profile (
     next_invoice_number INTEGER NOT NULL,
     next_debit_number INTEGER NOT NULL,
     next_credit_number INTEGER NOT NULL,
+    tax_rounding TEXT NOT NULL DEFAULT 'per_invoice', -- per_invoice or per_line
+    round_off TEXT NOT NULL DEFAULT 'nearest_rupee',
+    upi_id TEXT, -- shown as a payment QR on invoices when set
     updated_at TEXT
 );
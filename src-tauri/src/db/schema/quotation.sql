@@ -0,0 +1,33 @@
+CREATE TABLE IF NOT EXISTS quotation (
+    id TEXT PRIMARY KEY NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'quotation',
+    quotation_number TEXT NOT NULL,
+    quotation_date TEXT NOT NULL,
+    valid_until TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'sent',
+    company_id TEXT,
+    issuer_name TEXT NOT NULL,
+    issuer_address TEXT NOT NULL,
+    issuer_gst_number TEXT NOT NULL,
+    issuer_phone TEXT NOT NULL,
+    issuer_email TEXT,
+    recipient_name TEXT NOT NULL,
+    recipient_address TEXT NOT NULL,
+    recipient_gst_number TEXT NOT NULL,
+    recipient_phone TEXT NOT NULL,
+    recipient_email TEXT,
+    place_of_supply TEXT NOT NULL,
+    items_json TEXT NOT NULL,
+    amount INTEGER NOT NULL, -- paise
+    cgst_percentage INTEGER, -- hundredths of a percent
+    sgst_percentage INTEGER,
+    igst_percentage INTEGER,
+    additional_charges_json TEXT,
+    total_amount INTEGER NOT NULL,
+    notes TEXT,
+    converted_invoice_id TEXT,
+    created_at TEXT NOT NULL,
+    discount_json TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_quotation_company ON quotation (company_id);
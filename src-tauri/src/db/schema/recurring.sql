@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS recurring_schedule (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    company_id TEXT NOT NULL,
+    frequency TEXT NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT,
+    day_of_month INTEGER NOT NULL,
+    next_run_date TEXT,
+    active BOOLEAN NOT NULL DEFAULT 1,
+    template_json TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS recurring_run_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    schedule_id TEXT NOT NULL,
+    run_date TEXT NOT NULL,
+    invoice_id TEXT,
+    invoice_number TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_recurring_run_log_schedule ON recurring_run_log (schedule_id);
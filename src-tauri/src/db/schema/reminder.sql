@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS reminder_rule (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    days_after_due INTEGER NOT NULL, -- negative for before the due date
+    repeat_every_days INTEGER,
+    active BOOLEAN NOT NULL DEFAULT 1,
+    subject_template TEXT,
+    body_template TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reminder_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    rule_id TEXT NOT NULL,
+    invoice_id TEXT NOT NULL,
+    reminder_date TEXT NOT NULL,
+    recipients TEXT NOT NULL, -- comma separated
+    status TEXT NOT NULL, -- sent, failed or skipped
+    error TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminder_log_invoice ON reminder_log (invoice_id);
+-- A reminder goes out once, failed attempts are retried
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reminder_log_once ON reminder_log (rule_id, invoice_id, reminder_date)
+    WHERE status != 'failed';
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS stock_movement (
+    id TEXT PRIMARY KEY NOT NULL,
+    product_id TEXT NOT NULL REFERENCES product(id),
+    movement_date TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    reference TEXT,
+    invoice_id TEXT REFERENCES invoice(id),
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_movement_product ON stock_movement (product_id, movement_date);
+CREATE INDEX IF NOT EXISTS idx_stock_movement_invoice ON stock_movement (invoice_id);
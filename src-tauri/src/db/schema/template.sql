@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS invoice_template (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    company_id TEXT NOT NULL,
+    invoice_json TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (company_id, name)
+);
@@ -1,6 +1,6 @@
 use tauri::command;
 
-use crate::{commands::{company::{add_company, search_company}, invoice::{create_invoice, get_invoice_ids, search_invoices}, user::{complete_onboarding, get_profile_details, is_logged_in, is_onboarded, login, logout, signup_user}}, db::{init_db, init_global_db}, utils::get_app_data_path};
+use crate::{commands::{challan::{cancel_delivery_challan, create_delivery_challan, get_challan_series, invoice_challans, search_delivery_challans, update_challan_series}, company::{add_company, delete_company_address, delete_company_contact, get_company_contacts, get_contact_emails, save_company_contact, get_company_addresses, save_company_address, search_company}, email::{get_email_log, get_email_settings, save_email_settings, send_invoice_email}, export::export_invoices, format::{amount_to_words, format_indian_amount}, invoice::{calculate_invoice_totals, cancel_invoice, create_invoice, duplicate_invoice, get_invoice_ids, search_invoices}, reminder::{delete_reminder_rule, get_reminder_log, get_reminder_rules, run_payment_reminders, save_reminder_rule, spawn_due_payment_reminders}, payment::{delete_payment, get_company_outstanding, get_invoice_balance, get_payments, record_payment}, product::{add_product, archive_product, search_products, update_product}, quotation::{convert_to_invoice, create_quotation, search_quotations, update_quotation_status}, recurring::{add_recurring_schedule, get_recurring_run_log, get_recurring_schedules, preview_recurring_runs, run_due_recurring_invoices, run_recurring_invoices, set_recurring_schedule_active, update_recurring_schedule}, report::{export_ageing_report, export_client_ledger, get_ageing_report, get_client_ledger}, state::{get_states, lookup_pincode}, upi::get_invoice_upi_qr, bank::{delete_bank_account, get_bank_accounts, lookup_ifsc, save_bank_account}, stock::{add_stock_entry, get_current_stock, get_stock_movements}, template::{delete_invoice_template, get_invoice_templates, invoice_from_template, save_invoice_template}, user::{complete_onboarding, get_profile_details, is_logged_in, is_onboarded, login, logout, signup_user}}, db::{init_db, init_global_db}, utils::get_app_data_path};
 pub mod db;
 pub mod models;
 pub mod commands;
@@ -20,17 +20,91 @@ pub fn run() {
             }
             crate::utils::init_app_data_path(&app.handle())?;
             init_global_db().expect("Failed to initialze app's global db");
-            // Should initilize when the user session file acutally exists
-            // init_db().expect("Failed to initialize the database");
+            // The user database is only known once there is a session
+            if is_logged_in().unwrap_or(false) {
+                init_db().expect("Failed to initialize the database");
+                // Raise recurring invoices that fell due while the app was closed
+                if let Err(e) = run_due_recurring_invoices() {
+                    log::warn!("Recurring invoices were not generated: {}", e);
+                }
+                // Then remind clients about invoices that have come due
+                spawn_due_payment_reminders(app.handle().clone());
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             greet,
             add_company,
             search_company,
+            save_company_address,
+            delete_company_address,
+            get_company_addresses,
+            save_company_contact,
+            delete_company_contact,
+            get_company_contacts,
+            get_contact_emails,
             search_invoices,
             create_invoice,
+            cancel_invoice,
+            duplicate_invoice,
+            calculate_invoice_totals,
             get_invoice_ids,
+            export_invoices,
+            get_email_settings,
+            save_email_settings,
+            send_invoice_email,
+            get_email_log,
+            save_reminder_rule,
+            delete_reminder_rule,
+            get_reminder_rules,
+            run_payment_reminders,
+            get_reminder_log,
+            get_invoice_upi_qr,
+            save_bank_account,
+            delete_bank_account,
+            get_bank_accounts,
+            lookup_ifsc,
+            amount_to_words,
+            format_indian_amount,
+            get_states,
+            lookup_pincode,
+            record_payment,
+            delete_payment,
+            get_payments,
+            get_invoice_balance,
+            get_company_outstanding,
+            get_ageing_report,
+            export_ageing_report,
+            get_client_ledger,
+            export_client_ledger,
+            add_product,
+            update_product,
+            archive_product,
+            search_products,
+            add_stock_entry,
+            get_current_stock,
+            get_stock_movements,
+            create_quotation,
+            update_quotation_status,
+            search_quotations,
+            convert_to_invoice,
+            create_delivery_challan,
+            cancel_delivery_challan,
+            search_delivery_challans,
+            get_challan_series,
+            update_challan_series,
+            invoice_challans,
+            add_recurring_schedule,
+            update_recurring_schedule,
+            set_recurring_schedule_active,
+            get_recurring_schedules,
+            run_recurring_invoices,
+            get_recurring_run_log,
+            preview_recurring_runs,
+            save_invoice_template,
+            delete_invoice_template,
+            get_invoice_templates,
+            invoice_from_template,
             get_profile_details,
             signup_user,
             login,
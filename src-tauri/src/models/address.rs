@@ -0,0 +1,91 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::state::{find_state, state_by_code, state_for_pincode, IndianState, OTHER_COUNTRIES};
+
+/// A postal address in the parts GST returns and e-invoices ask for.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Address {
+    pub line1: String,
+    #[serde(default)]
+    pub line2: Option<String>,
+    pub city: String,
+    #[serde(default)]
+    pub district: Option<String>,
+    #[serde(default)]
+    pub state_code: String, // GST state code, filled in from the pincode when left empty
+    pub pincode: String,
+}
+
+impl Address {
+    /// An address entered as one block of text, kept whole in the first line. The state
+    /// comes from the pincode, or failing that from the GSTIN.
+    pub fn from_legacy(address: &str, pincode: &str, gst_number: &str) -> Self {
+        Address {
+            line1: address.trim().to_string(),
+            pincode: pincode.trim().to_string(),
+            state_code: state_for_pincode(pincode)
+                .or_else(|| find_state(gst_number))
+                .map_or(String::new(), |state| state.code.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn state(&self) -> Option<&'static IndianState> {
+        state_by_code(&self.state_code)
+    }
+
+    /// Trims the parts and fills in a missing state from the pincode.
+    pub fn normalized(&self) -> Self {
+        let optional = |part: &Option<String>| part.as_deref().map(str::trim).filter(|p| !p.is_empty()).map(str::to_string);
+        let state_code = match self.state_code.trim() {
+            "" => state_for_pincode(&self.pincode).map_or(String::new(), |state| state.code.to_string()),
+            code => code.to_string(),
+        };
+
+        Address {
+            line1: self.line1.trim().to_string(),
+            line2: optional(&self.line2),
+            city: self.city.trim().to_string(),
+            district: optional(&self.district),
+            state_code,
+            pincode: self.pincode.trim().to_string(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.line1.trim().is_empty() || self.city.trim().is_empty() {
+            return Err("Address needs its first line and city".to_string());
+        }
+        if !is_pincode(&self.pincode) {
+            return Err(format!("{} is not a valid pincode", self.pincode));
+        }
+        match self.state() {
+            Some(state) if state.code != OTHER_COUNTRIES => Ok(()),
+            _ => Err(format!("{} is not a known state code", self.state_code)),
+        }
+    }
+}
+
+/// Six digits, the first of which is never zero.
+pub fn is_pincode(value: &str) -> bool {
+    value.len() == 6 && value.chars().all(|c| c.is_ascii_digit()) && !value.starts_with('0')
+}
+
+/// The parts on one line, e.g. "12 MG Road, Fort, Mumbai, Maharashtra - 400001".
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state().map(|state| state.name);
+        let parts: Vec<&str> = [Some(self.line1.as_str()), self.line2.as_deref(), Some(self.city.as_str()), self.district.as_deref(), state]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect();
+        write!(f, "{}", parts.join(", "))?;
+        if !self.pincode.is_empty() {
+            write!(f, " - {}", self.pincode)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::invoice::BankDetails;
+
+/// One of the business's bank accounts. Invoices copy the details of the account picked
+/// for them, or of the default one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BankAccount {
+    pub id: Option<String>,
+    #[serde(default)]
+    pub label: String, // e.g. "Current account", the bank name when left empty
+    pub bank_name: String,
+    pub branch: String,
+    pub account_name: String,
+    pub account_no: String,
+    pub ifsc_code: String,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+impl BankAccount {
+    /// The details as printed on an invoice.
+    pub fn details(&self) -> BankDetails {
+        BankDetails {
+            bank_name: self.bank_name.clone(),
+            branch: self.branch.clone(),
+            account_name: self.account_name.clone(),
+            account_no: self.account_no.clone(),
+            ifsc_code: self.ifsc_code.clone(),
+        }
+    }
+}
+
+/// What can be told about a branch from its IFSC alone.
+#[derive(Serialize, Debug, Clone)]
+pub struct IfscLookup {
+    pub ifsc_code: String,
+    pub bank_code: String,
+    pub bank_name: Option<&'static str>, // None for banks missing from the bundled list
+    pub branch_code: String,
+}
+
+/// Checks the account number and IFSC before they are saved or printed on an invoice.
+pub fn validate_bank_details(details: &BankDetails) -> Result<(), String> {
+    if details.account_no.trim().is_empty() || !details.account_no.trim().chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("{} is not a valid account number", details.account_no));
+    }
+    if !is_ifsc(&details.ifsc_code) {
+        return Err(format!("{} is not a valid IFSC", details.ifsc_code));
+    }
+    Ok(())
+}
+
+/// Four letters for the bank, a zero, then six letters or digits for the branch.
+pub fn is_ifsc(value: &str) -> bool {
+    let value = value.as_bytes();
+    value.len() == 11
+        && value[..4].iter().all(u8::is_ascii_uppercase)
+        && value[4] == b'0'
+        && value[5..].iter().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// IFSC bank codes of the banks most businesses bank with, sorted by code.
+const BANK_CODES: [(&str, &str); 47] = [
+    ("ABHY", "Abhyudaya Co-operative Bank"),
+    ("AIRP", "Airtel Payments Bank"),
+    ("AUBL", "AU Small Finance Bank"),
+    ("BARB", "Bank of Baroda"),
+    ("BDBL", "Bandhan Bank"),
+    ("BKID", "Bank of India"),
+    ("CBIN", "Central Bank of India"),
+    ("CITI", "Citibank"),
+    ("CIUB", "City Union Bank"),
+    ("CNRB", "Canara Bank"),
+    ("COSB", "Cosmos Co-operative Bank"),
+    ("CSBK", "CSB Bank"),
+    ("DBSS", "DBS Bank India"),
+    ("DCBL", "DCB Bank"),
+    ("DEUT", "Deutsche Bank"),
+    ("DLXB", "Dhanlaxmi Bank"),
+    ("ESFB", "Equitas Small Finance Bank"),
+    ("FDRL", "Federal Bank"),
+    ("HDFC", "HDFC Bank"),
+    ("HSBC", "HSBC"),
+    ("IBKL", "IDBI Bank"),
+    ("ICIC", "ICICI Bank"),
+    ("IDFB", "IDFC FIRST Bank"),
+    ("IDIB", "Indian Bank"),
+    ("INDB", "IndusInd Bank"),
+    ("IOBA", "Indian Overseas Bank"),
+    ("IPOS", "India Post Payments Bank"),
+    ("JAKA", "Jammu and Kashmir Bank"),
+    ("KARB", "Karnataka Bank"),
+    ("KKBK", "Kotak Mahindra Bank"),
+    ("KVBL", "Karur Vysya Bank"),
+    ("MAHB", "Bank of Maharashtra"),
+    ("NKGS", "NKGSB Co-operative Bank"),
+    ("PSIB", "Punjab and Sind Bank"),
+    ("PUNB", "Punjab National Bank"),
+    ("PYTM", "Paytm Payments Bank"),
+    ("RATN", "RBL Bank"),
+    ("SBIN", "State Bank of India"),
+    ("SCBL", "Standard Chartered Bank"),
+    ("SIBL", "South Indian Bank"),
+    ("SRCB", "Saraswat Co-operative Bank"),
+    ("SVCB", "SVC Co-operative Bank"),
+    ("TMBL", "Tamilnad Mercantile Bank"),
+    ("UBIN", "Union Bank of India"),
+    ("UCBA", "UCO Bank"),
+    ("UTIB", "Axis Bank"),
+    ("YESB", "Yes Bank"),
+];
+
+/// Splits an IFSC into bank and branch codes and names the bank when it is a known one.
+pub fn lookup_ifsc(ifsc: &str) -> Option<IfscLookup> {
+    let ifsc = ifsc.trim().to_ascii_uppercase();
+    if !is_ifsc(&ifsc) {
+        return None;
+    }
+    let bank_code = &ifsc[..4];
+
+    Some(IfscLookup {
+        bank_code: bank_code.to_string(),
+        bank_name: BANK_CODES
+            .binary_search_by(|(code, _)| code.cmp(&bank_code))
+            .ok()
+            .map(|index| BANK_CODES[index].1),
+        branch_code: ifsc[5..].to_string(),
+        ifsc_code: ifsc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_ifsc_checks_the_shape() {
+        let cases = [
+            ("HDFC0001234", true),
+            ("SBIN0ABC12Z", true),
+            ("HDFC1001234", false), // Fifth character must be zero
+            ("HDFCO001234", false), // Letter O, not zero
+            ("hdfc0001234", false),
+            ("HDFC0abc123", false),
+            ("HDF00001234", false),
+            ("HDFC000123", false),
+            ("HDFC00012345", false),
+            (" HDFC0001234", false),
+            ("HDFC-001234", false),
+            ("", false),
+        ];
+        for (value, valid) in cases {
+            assert_eq!(is_ifsc(value), valid, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn lookup_ifsc_names_known_banks() {
+        let found = lookup_ifsc(" hdfc0001234 ").unwrap();
+        assert_eq!(found.ifsc_code, "HDFC0001234");
+        assert_eq!(found.bank_code, "HDFC");
+        assert_eq!(found.bank_name, Some("HDFC Bank"));
+        assert_eq!(found.branch_code, "001234");
+
+        assert_eq!(lookup_ifsc("ZZZZ0001234").unwrap().bank_name, None);
+        assert!(lookup_ifsc("HDFC1001234").is_none());
+        // The lookup is a binary search
+        assert!(BANK_CODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
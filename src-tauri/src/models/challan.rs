@@ -0,0 +1,110 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::models::invoice::{InvoiceItem, TransportDetails};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallanPurpose {
+    #[default]
+    OnApproval,
+    JobWork,
+    Other,
+}
+
+impl ChallanPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallanPurpose::OnApproval => "on_approval",
+            ChallanPurpose::JobWork => "job_work",
+            ChallanPurpose::Other => "other",
+        }
+    }
+}
+
+impl ToSql for ChallanPurpose {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ChallanPurpose {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "on_approval" => Ok(ChallanPurpose::OnApproval),
+            "job_work" => Ok(ChallanPurpose::JobWork),
+            "other" => Ok(ChallanPurpose::Other),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallanStatus {
+    #[default]
+    Open,
+    Invoiced,
+    Cancelled,
+}
+
+impl ChallanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallanStatus::Open => "open",
+            ChallanStatus::Invoiced => "invoiced",
+            ChallanStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl ToSql for ChallanStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ChallanStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "open" => Ok(ChallanStatus::Open),
+            "invoiced" => Ok(ChallanStatus::Invoiced),
+            "cancelled" => Ok(ChallanStatus::Cancelled),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Goods sent out without a sale yet. A challan carries no tax; the invoice raised
+/// against it later does. Item rates are only the declared value of the goods.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeliveryChallan {
+    pub id: Option<String>,
+    #[serde(default)]
+    pub challan_number: String, // Assigned from the challan series when the challan is created
+    pub challan_date: String,
+    #[serde(default)]
+    pub purpose: ChallanPurpose,
+    #[serde(default)]
+    pub status: ChallanStatus,
+    pub company_id: Option<String>,
+    pub issuer_name: String,
+    pub issuer_address: String,
+    pub issuer_gst_number: String,
+    pub issuer_phone: String,
+    pub recipient_name: String,
+    pub recipient_address: String,
+    pub recipient_gst_number: String,
+    pub recipient_phone: String,
+    pub transport_details: TransportDetails,
+    pub items: Vec<InvoiceItem>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub invoice_id: Option<String>, // Invoice the challan was billed on
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChallanSeries {
+    pub prefix: String,
+    pub next_number: u32,
+}
@@ -1,13 +1,97 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 
+use crate::models::address::Address;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Company {
     pub id: Option<String>,
     pub name: String,
     pub owner_name: String,
-    pub address: String,
+    pub address: String, // The whole address on one line
     pub pincode: String,
+    #[serde(default)]
+    pub address_details: Option<Address>,
     pub gst_number: String,
     pub phone: String,
     pub email: Option<String>,
+    #[serde(default)]
+    pub payment_terms_days: Option<u32>, // Credit period used to compute due dates
+}
+
+/// Another address kept for a client, e.g. a warehouse goods are shipped to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompanyAddress {
+    pub id: Option<String>,
+    pub company_id: String,
+    pub label: String, // e.g. "Head office", "Bhiwandi warehouse"
+    pub address: Address,
+    #[serde(default)]
+    pub gst_number: Option<String>, // When the location is registered on its own
+    #[serde(default)]
+    pub kind: AddressKind,
+    #[serde(default)]
+    pub is_default: bool, // Used on new invoices when no address is picked
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressKind {
+    Billing,
+    Shipping,
+    #[default]
+    Both,
+}
+
+impl AddressKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressKind::Billing => "billing",
+            AddressKind::Shipping => "shipping",
+            AddressKind::Both => "both",
+        }
+    }
+
+    pub fn for_billing(&self) -> bool {
+        matches!(self, AddressKind::Billing | AddressKind::Both)
+    }
+
+    pub fn for_shipping(&self) -> bool {
+        matches!(self, AddressKind::Shipping | AddressKind::Both)
+    }
+}
+
+impl ToSql for AddressKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for AddressKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "billing" => Ok(AddressKind::Billing),
+            "shipping" => Ok(AddressKind::Shipping),
+            "both" => Ok(AddressKind::Both),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A person at a client, and which documents they should be sent.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompanyContact {
+    pub id: Option<String>,
+    pub company_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub role: Option<String>, // e.g. "Accounts", "Purchase manager"
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub receives_invoices: bool,
+    #[serde(default)]
+    pub receives_reminders: bool, // Payment reminders, usually the accounts team
 }
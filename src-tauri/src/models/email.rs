@@ -0,0 +1,111 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+/// Outgoing mail server and the templates invoices are sent with. Templates can use
+/// `{invoice_number}`, `{invoice_date}`, `{document_type}`, `{total}`,
+/// `{amount_in_words}`, `{recipient_name}` and `{issuer_name}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmailSettings {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>, // Never sent back to the frontend, left as is when saved empty
+    pub from_name: String,
+    pub from_email: String,
+    #[serde(default)]
+    pub subject_template: Option<String>, // Default subject when not set
+    #[serde(default)]
+    pub body_template: Option<String>,
+}
+
+pub const DEFAULT_SUBJECT_TEMPLATE: &str = "{document_type} {invoice_number} from {issuer_name}";
+pub const DEFAULT_BODY_TEMPLATE: &str = "Dear {recipient_name},
+
+Please find attached {document_type} {invoice_number} dated {invoice_date} for {total} ({amount_in_words}).
+
+Regards,
+{issuer_name}";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    None, // Plain text, only for a server on this machine
+    #[default]
+    StartTls, // Usually port 587
+    Tls, // Usually port 465
+}
+
+impl SmtpSecurity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpSecurity::None => "none",
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::Tls => "tls",
+        }
+    }
+}
+
+impl ToSql for SmtpSecurity {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for SmtpSecurity {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "none" => Ok(SmtpSecurity::None),
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// One attempt at emailing an invoice, successful or not.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmailLogEntry {
+    pub id: String,
+    pub invoice_id: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub status: EmailStatus,
+    pub error: Option<String>,
+    pub sent_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailStatus {
+    Sent,
+    Failed,
+}
+
+impl EmailStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailStatus::Sent => "sent",
+            EmailStatus::Failed => "failed",
+        }
+    }
+}
+
+impl ToSql for EmailStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for EmailStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "sent" => Ok(EmailStatus::Sent),
+            "failed" => Ok(EmailStatus::Failed),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
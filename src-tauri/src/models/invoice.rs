@@ -1,23 +1,112 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 
+use crate::models::{
+    address::Address,
+    money::{Money, Percent},
+    state::{find_state, IndianState},
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InvoiceItem {
     pub description: String,
     pub hsn_code: String, // Harmonized System Nomenclature code
     pub quantity: f64,
     pub unit: String,
-    pub rate: f64,
-    pub amount: f64,
+    pub rate: Money,
+    pub amount: Money, // Quantity times rate, before any discount
+    #[serde(default)]
+    pub discount: Option<Discount>,
+    #[serde(default)]
+    pub cess: Option<Cess>,
+    #[serde(default)]
+    pub product_id: Option<String>, // Catalog entry the line was picked from
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl InvoiceItem {
+    pub fn discount_amount(&self) -> Money {
+        self.discount.map_or(Money::ZERO, |discount| discount.amount_on(self.amount))
+    }
+
+    /// Line amount after its discount.
+    pub fn net_amount(&self) -> Money {
+        self.amount - self.discount_amount()
+    }
+
+    /// Cess on the line amount after its discount.
+    pub fn cess_amount(&self) -> Money {
+        self.cess.map_or(Money::ZERO, |cess| cess.amount_on(self.net_amount(), self.quantity))
+    }
+}
+
+/// Compensation cess on a line: a percentage of its value, a fixed amount per unit, or both.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Cess {
+    #[serde(default)]
+    pub ad_valorem: Option<Percent>,
+    #[serde(default)]
+    pub specific: Option<Money>, // Per unit of quantity
+}
+
+impl Cess {
+    pub fn amount_on(&self, value: Money, quantity: f64) -> Money {
+        let ad_valorem = self.ad_valorem.map_or(Money::ZERO, |rate| value.percent(rate));
+        let specific = self.specific.map_or(Money::ZERO, |per_unit| per_unit.times(quantity));
+        ad_valorem + specific
+    }
+}
+
+/// A discount on a line or on the whole invoice. GST is charged on the value after it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum Discount {
+    Percent(Percent),
+    Flat(Money),
+}
+
+impl Discount {
+    /// The discount taken off `amount`.
+    pub fn amount_on(&self, amount: Money) -> Money {
+        match *self {
+            Discount::Percent(rate) => amount.percent(rate),
+            Discount::Flat(flat) => flat,
+        }
+    }
+
+    /// Rejects negative discounts and ones larger than what they're taken off.
+    pub fn validate(&self, amount: Money) -> Result<(), String> {
+        match *self {
+            Discount::Percent(rate) if rate < Percent::ZERO || rate > Percent::HUNDRED => {
+                Err(format!("Discount of {}% must be between 0% and 100%", rate))
+            }
+            Discount::Flat(flat) if flat < Money::ZERO => Err(format!("Discount of {} cannot be negative", flat)),
+            Discount::Flat(flat) if flat > amount => {
+                Err(format!("Discount of {} is more than the amount of {}", flat, amount))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AdditionalCharges{
     pub description: String,
-    pub amount: f64,
+    pub amount: Money,
+    #[serde(default = "taxable_by_default")]
+    pub taxable: bool, // Freight and packing billed with goods are usually taxed with them
+    #[serde(default)]
+    pub hsn_code: Option<String>, // HSN/SAC, e.g. 996511 for freight
+    #[serde(default)]
+    pub gst_rate: Option<Percent>, // Own GST rate, otherwise taxed at the invoice's rates
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// Charges saved before the flag existed were always part of the taxable value
+fn taxable_by_default() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct BankDetails {
     pub bank_name: String,
     pub branch: String,
@@ -26,16 +115,338 @@ pub struct BankDetails {
     pub ifsc_code: String
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TransportDetails {
-    transporter_name: String,
-    place_of_supply: String,
-    vehicle_no: Option<String>,
-    station: Option<String>,
-    eway_bill_no: Option<String>
+    pub transporter_name: String,
+    #[serde(default)]
+    pub place_of_supply: String, // Older invoices kept it only here, see Invoice::place_of_supply
+    pub vehicle_no: Option<String>,
+    pub station: Option<String>,
+    pub eway_bill_no: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    #[default]
+    Unpaid,
+    PartiallyPaid,
+    Paid,
+    Cancelled,
+}
+
+impl InvoiceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvoiceStatus::Unpaid => "unpaid",
+            InvoiceStatus::PartiallyPaid => "partially_paid",
+            InvoiceStatus::Paid => "paid",
+            InvoiceStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InvoiceStatus::Unpaid => "Unpaid",
+            InvoiceStatus::PartiallyPaid => "Partially Paid",
+            InvoiceStatus::Paid => "Paid",
+            InvoiceStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    /// Status implied by how much of the invoice total has been settled.
+    pub fn from_settlement(total: Money, settled: Money) -> Self {
+        if settled <= Money::ZERO {
+            InvoiceStatus::Unpaid
+        } else if settled >= total {
+            InvoiceStatus::Paid
+        } else {
+            InvoiceStatus::PartiallyPaid
+        }
+    }
+}
+
+impl ToSql for InvoiceStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for InvoiceStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "unpaid" => Ok(InvoiceStatus::Unpaid),
+            "partially_paid" => Ok(InvoiceStatus::PartiallyPaid),
+            "paid" => Ok(InvoiceStatus::Paid),
+            "cancelled" => Ok(InvoiceStatus::Cancelled),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    #[default]
+    TaxInvoice,
+    CreditNote,
+    DebitNote,
+}
+
+impl DocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::TaxInvoice => "tax_invoice",
+            DocumentType::CreditNote => "credit_note",
+            DocumentType::DebitNote => "debit_note",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DocumentType::TaxInvoice => "Tax Invoice",
+            DocumentType::CreditNote => "Credit Note",
+            DocumentType::DebitNote => "Debit Note",
+        }
+    }
+}
+
+impl ToSql for DocumentType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for DocumentType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "tax_invoice" => Ok(DocumentType::TaxInvoice),
+            "credit_note" => Ok(DocumentType::CreditNote),
+            "debit_note" => Ok(DocumentType::DebitNote),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// GST classification of the supply. Exports and supplies to SEZ units are zero rated:
+/// either IGST is paid (and refunded later) or they go out under a LUT or bond without tax.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyType {
+    #[default]
+    Regular,
+    SezWithPayment,
+    SezWithoutPayment,
+    ExportWithPayment,
+    ExportWithoutPayment,
+    DeemedExport,
+}
+
+impl SupplyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupplyType::Regular => "regular",
+            SupplyType::SezWithPayment => "sez_with_payment",
+            SupplyType::SezWithoutPayment => "sez_without_payment",
+            SupplyType::ExportWithPayment => "export_with_payment",
+            SupplyType::ExportWithoutPayment => "export_without_payment",
+            SupplyType::DeemedExport => "deemed_export",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SupplyType::Regular => "Regular",
+            SupplyType::SezWithPayment => "SEZ with payment",
+            SupplyType::SezWithoutPayment => "SEZ without payment",
+            SupplyType::ExportWithPayment => "Export with payment",
+            SupplyType::ExportWithoutPayment => "Export without payment",
+            SupplyType::DeemedExport => "Deemed export",
+        }
+    }
+
+    /// Zero rated under a LUT or bond, so no GST is charged.
+    pub fn is_without_payment(&self) -> bool {
+        matches!(self, SupplyType::SezWithoutPayment | SupplyType::ExportWithoutPayment)
+    }
+
+    /// Exports and SEZ supplies, which are inter-state whatever the place of supply.
+    pub fn is_zero_rated(&self) -> bool {
+        !matches!(self, SupplyType::Regular | SupplyType::DeemedExport)
+    }
+
+    pub fn is_export(&self) -> bool {
+        matches!(self, SupplyType::ExportWithPayment | SupplyType::ExportWithoutPayment)
+    }
+}
+
+impl ToSql for SupplyType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for SupplyType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "regular" => Ok(SupplyType::Regular),
+            "sez_with_payment" => Ok(SupplyType::SezWithPayment),
+            "sez_without_payment" => Ok(SupplyType::SezWithoutPayment),
+            "export_with_payment" => Ok(SupplyType::ExportWithPayment),
+            "export_without_payment" => Ok(SupplyType::ExportWithoutPayment),
+            "deemed_export" => Ok(SupplyType::DeemedExport),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// Who goods are delivered to when that isn't the recipient's billing address.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ShipTo {
+    pub name: String,
+    #[serde(default)]
+    pub gst_number: Option<String>,
+    pub address: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ShippingBill {
+    pub number: String,
+    pub date: String, // YYYY-MM-DD
+    pub port_code: String,
+}
+
+/// Currency an invoice is billed in when it isn't rupees. Every amount on the invoice
+/// is then in this currency, in hundredths of its unit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForeignCurrency {
+    pub code: String,       // ISO 4217, e.g. USD
+    pub exchange_rate: f64, // Rupees per unit of the currency
+}
+
+/// Whether tax is worked out on each line and added up, or once on the invoice's taxable
+/// value. The two can differ by a few paise.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxRounding {
+    #[default]
+    PerInvoice,
+    PerLine,
+}
+
+impl TaxRounding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxRounding::PerInvoice => "per_invoice",
+            TaxRounding::PerLine => "per_line",
+        }
+    }
+}
+
+impl ToSql for TaxRounding {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for TaxRounding {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "per_invoice" => Ok(TaxRounding::PerInvoice),
+            "per_line" => Ok(TaxRounding::PerLine),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundOff {
+    None,
+    #[default]
+    NearestRupee, // Invoice total rounded to a whole rupee, the difference shown as round-off
+    Up,   // Always to the next whole rupee
+    Down, // Always to the whole rupee below
+}
+
+impl RoundOff {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoundOff::None => "none",
+            RoundOff::NearestRupee => "nearest_rupee",
+            RoundOff::Up => "up",
+            RoundOff::Down => "down",
+        }
+    }
+}
+
+impl ToSql for RoundOff {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for RoundOff {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "none" => Ok(RoundOff::None),
+            "nearest_rupee" => Ok(RoundOff::NearestRupee),
+            "up" => Ok(RoundOff::Up),
+            "down" => Ok(RoundOff::Down),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// How invoice totals are rounded, kept on the profile.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct RoundingRules {
+    #[serde(default)]
+    pub tax_rounding: TaxRounding,
+    #[serde(default)]
+    pub round_off: RoundOff,
+}
+
+/// Which taxes the invoice's GST rates become on its lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaxSplit {
+    /// Supplies under a LUT or bond carry no tax.
+    NoTax,
+    Igst(Percent),
+    CgstSgst(Percent, Percent),
+    /// Only IGST filled in on an intra-state supply, CGST and SGST take half each.
+    HalvedIgst(Percent),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TaxSummary {
+    pub taxable_value: Money,
+    pub cgst: Money,
+    pub sgst: Money,
+    pub igst: Money,
+    pub cess: Money,
+    pub total_tax: Money,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+pub struct InvoiceTotals {
+    pub discount: Money, // Line and invoice discounts together
+    pub taxable_value: Money,
+    pub non_taxable_charges: Money,
+    pub interstate: bool, // IGST rather than CGST and SGST
+    pub cgst: Money,
+    pub sgst: Money,
+    pub igst: Money,
+    pub cess: Money,
+    pub total_tax: Money,
+    pub tcs: Money,
+    pub round_off: Money,
+    pub total: Money,
+    pub tds_expected: Money, // Not part of the total, the client deducts it when paying
+    pub total_inr: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Invoice {
     pub id: Option<String>,
     pub issuer_name: String,
@@ -43,23 +454,69 @@ pub struct Invoice {
     pub issuer_gst_number: String,
     pub issuer_phone: String,
     pub issuer_email: Option<String>, // Optional email address
+    #[serde(default)]
+    pub issuer_address_details: Option<Address>, // issuer_address in parts
     pub recipient_name: String,
     pub recipient_address: String,
     pub recipient_gst_number: String,
     pub recipient_phone: String,
     pub recipient_email: Option<String>, // Optional email address
+    #[serde(default)]
+    pub recipient_address_details: Option<Address>, // Bill-to address
+    #[serde(default)]
+    pub bill_to_address_id: Option<String>, // Address book entry the bill-to address was copied from
+    #[serde(default)]
+    pub ship_to: Option<ShipTo>, // None when goods go to the bill-to address
+    #[serde(default)]
+    pub ship_to_address_id: Option<String>,
     pub invoice_number: String,
     pub invoice_date: String, // Consider using chrono::NaiveDate for better date handling
-    pub amount: f64,
-    pub cgst_percentage: Option<f64>,
-    pub sgst_percentage: Option<f64>,
-    pub igst_percentage: Option<f64>,
+    pub amount: Money,
+    pub cgst_percentage: Option<Percent>,
+    pub sgst_percentage: Option<Percent>,
+    pub igst_percentage: Option<Percent>,
     pub additional_charges: Option<Vec<AdditionalCharges>>, // Optional additional charges
+    #[serde(default)]
+    pub discount: Option<Discount>, // On the items after their own discounts
     pub bank_details: BankDetails,
+    #[serde(default)]
+    pub bank_account_id: Option<String>, // Account the bank details were copied from, the default when not picked
     pub transport_details: TransportDetails,
-    pub total: f64,
+    #[serde(default)]
+    pub place_of_supply: Option<String>, // GST state code, 96 for exports
+    pub total: Money,
+    #[serde(default)]
+    pub round_off: Money, // Added to taxable value plus tax to reach the total
+    #[serde(default)]
+    pub rounding_rules: Option<RoundingRules>, // Profile rules the invoice was issued under, None on drafts
+    #[serde(default)]
+    pub tcs_percentage: Option<Percent>, // TCS collected on the invoice value including GST
+    #[serde(default)]
+    pub tcs_amount: Money,
+    #[serde(default)]
+    pub tds_percentage: Option<Percent>, // TDS the client is expected to deduct on the value before GST
+    #[serde(default)]
+    pub tds_expected: Money,
+    #[serde(default)]
+    pub supply_type: SupplyType,
+    #[serde(default)]
+    pub lut_reference: Option<String>, // LUT ARN or bond number for supplies without payment
+    #[serde(default)]
+    pub shipping_bill: Option<ShippingBill>,
+    #[serde(default)]
+    pub foreign_currency: Option<ForeignCurrency>, // None for rupee invoices
+    #[serde(default)]
+    pub total_inr: Money, // Total in rupees, which is what payments and reports go by
     pub items: Vec<InvoiceItem>,
-    pub reverse_charge: bool
+    pub reverse_charge: bool,
+    #[serde(default)]
+    pub company_id: Option<String>, // Client the invoice was raised against
+    #[serde(default)]
+    pub status: InvoiceStatus,
+    #[serde(default)]
+    pub document_type: DocumentType,
+    #[serde(default)]
+    pub original_invoice_id: Option<String> // Invoice a credit/debit note was raised against
 }
 
 impl Invoice {
@@ -77,11 +534,11 @@ impl Invoice {
         recipient_email: Option<String>,
         invoice_number: String,
         invoice_date: String,
-        amount: f64,
-        cgst_percentage: Option<f64>,
-        sgst_percentage: Option<f64>,
-        igst_percentage: Option<f64>,
-        total: f64,
+        amount: Money,
+        cgst_percentage: Option<Percent>,
+        sgst_percentage: Option<Percent>,
+        igst_percentage: Option<Percent>,
+        total: Money,
         items: Vec<InvoiceItem>,
         bank_details: BankDetails,
         transport_details: TransportDetails,
@@ -94,11 +551,16 @@ impl Invoice {
             issuer_gst_number,
             issuer_phone,
             issuer_email,
+            issuer_address_details: None,
             recipient_name,
             recipient_address,
             recipient_gst_number,
             recipient_phone,
             recipient_email,
+            recipient_address_details: None,
+            bill_to_address_id: None,
+            ship_to: None,
+            ship_to_address_id: None,
             invoice_number,
             invoice_date,
             amount,
@@ -106,11 +568,218 @@ impl Invoice {
             sgst_percentage,
             igst_percentage,
             additional_charges: None, // Initialize with no additional charges
+            discount: None,
+            place_of_supply: find_state(&transport_details.place_of_supply).map(|state| state.code.to_string()),
             total,
+            round_off: Money::ZERO,
+            tcs_percentage: None,
+            tcs_amount: Money::ZERO,
+            tds_percentage: None,
+            tds_expected: Money::ZERO,
+            supply_type: SupplyType::Regular,
+            lut_reference: None,
+            shipping_bill: None,
+            foreign_currency: None,
+            total_inr: Money::ZERO,
             items,
             bank_details,
+            bank_account_id: None,
             transport_details,
-            reverse_charge
+            reverse_charge,
+            company_id: None,
+            status: InvoiceStatus::Unpaid,
+            document_type: DocumentType::TaxInvoice,
+            original_invoice_id: None,
+            rounding_rules: None
+        }
+    }
+
+    /// The place of supply from the state master, read from the transport details on
+    /// invoices saved before it had a field of its own.
+    pub fn place_of_supply_state(&self) -> Option<&'static IndianState> {
+        match &self.place_of_supply {
+            Some(code) => find_state(code),
+            None => find_state(&self.transport_details.place_of_supply),
+        }
+    }
+
+    /// Supply is inter-state when the place of supply differs from the state in the
+    /// issuer's GSTIN. Falls back to the percentages that were filled in when either
+    /// state is unknown.
+    pub fn is_interstate(&self) -> bool {
+        if self.supply_type.is_zero_rated() {
+            return true;
+        }
+
+        let issuer_state = find_state(&self.issuer_gst_number);
+        let supply_state = self.place_of_supply_state();
+
+        match (issuer_state, supply_state) {
+            (Some(issuer), Some(supply)) => issuer.code != supply.code,
+            _ => self.igst_percentage.is_some()
+                && self.cgst_percentage.is_none()
+                && self.sgst_percentage.is_none(),
+        }
+    }
+
+    /// Items after line discounts, before the invoice discount.
+    pub fn items_subtotal(&self) -> Money {
+        self.items.iter().map(|item| item.net_amount()).sum()
+    }
+
+    pub fn invoice_discount(&self) -> Money {
+        self.discount.map_or(Money::ZERO, |discount| discount.amount_on(self.items_subtotal()))
+    }
+
+    /// Every discount on the invoice, line and invoice level.
+    pub fn discount_total(&self) -> Money {
+        self.items.iter().map(|item| item.discount_amount()).sum::<Money>() + self.invoice_discount()
+    }
+
+    /// Items value after all discounts, which is what gets stored as the invoice amount.
+    pub fn items_value(&self) -> Money {
+        self.items_subtotal() - self.invoice_discount()
+    }
+
+    /// Taxable value is the discounted items value plus the taxable additional charges.
+    pub fn taxable_value(&self) -> Money {
+        self.items_value() + self.charges().filter(|charge| charge.taxable).map(|charge| charge.amount).sum()
+    }
+
+    pub fn charges_total(&self) -> Money {
+        self.charges().map(|charge| charge.amount).sum()
+    }
+
+    /// Charges billed without GST, added to the total after tax.
+    pub fn non_taxable_charges(&self) -> Money {
+        self.charges().filter(|charge| !charge.taxable).map(|charge| charge.amount).sum()
+    }
+
+    fn charges(&self) -> impl Iterator<Item = &AdditionalCharges> {
+        self.additional_charges.iter().flatten()
+    }
+
+    /// Converts an amount on the invoice to rupees at the invoice's exchange rate.
+    pub fn to_inr(&self, amount: Money) -> Money {
+        match &self.foreign_currency {
+            Some(currency) => amount.times(currency.exchange_rate),
+            None => amount,
+        }
+    }
+
+    /// How the invoice rates apply: CGST/SGST or IGST depending on the place of supply
+    /// and whichever of the rates were filled in.
+    pub fn tax_split(&self) -> TaxSplit {
+        if self.supply_type.is_without_payment() {
+            return TaxSplit::NoTax;
+        }
+
+        let interstate = self.is_interstate();
+        match (self.cgst_percentage, self.sgst_percentage, self.igst_percentage) {
+            (_, _, Some(igst)) if interstate => TaxSplit::Igst(igst),
+            (cgst, sgst, None) if interstate => TaxSplit::Igst(cgst.unwrap_or_default() + sgst.unwrap_or_default()),
+            (None, None, Some(igst)) => TaxSplit::HalvedIgst(igst),
+            (cgst, sgst, _) => TaxSplit::CgstSgst(cgst.unwrap_or_default(), sgst.unwrap_or_default()),
+        }
+    }
+
+    /// Splits the invoice tax as `tax_split` says, rounding either once on the taxable
+    /// value or on every line. Charges with a GST rate of their own are always taxed
+    /// line by line. Supplies under a LUT or bond carry no tax.
+    pub fn tax_summary(&self, rounding: TaxRounding) -> TaxSummary {
+        let split = self.tax_split();
+        if split == TaxSplit::NoTax {
+            return TaxSummary {
+                taxable_value: self.taxable_value(),
+                cgst: Money::ZERO,
+                sgst: Money::ZERO,
+                igst: Money::ZERO,
+                cess: Money::ZERO,
+                total_tax: Money::ZERO,
+            };
+        }
+
+        let (at_invoice_rates, at_own_rate): (Vec<&AdditionalCharges>, Vec<&AdditionalCharges>) = self
+            .charges()
+            .filter(|charge| charge.taxable)
+            .partition(|charge| charge.gst_rate.is_none());
+
+        let lines: Vec<Money> = match rounding {
+            TaxRounding::PerInvoice => {
+                vec![self.items_value() + at_invoice_rates.iter().map(|charge| charge.amount).sum()]
+            }
+            // The invoice discount counts as one more (negative) line
+            TaxRounding::PerLine => self
+                .items
+                .iter()
+                .map(|item| item.net_amount())
+                .chain(std::iter::once(-self.invoice_discount()))
+                .chain(at_invoice_rates.iter().map(|charge| charge.amount))
+                .collect(),
+        };
+        let tax_at = |rate: Percent| -> Money { lines.iter().map(|amount| amount.percent(rate)).sum() };
+
+        let (mut cgst, mut sgst, mut igst) = match split {
+            TaxSplit::NoTax => (Money::ZERO, Money::ZERO, Money::ZERO),
+            TaxSplit::Igst(igst) => (Money::ZERO, Money::ZERO, tax_at(igst)),
+            TaxSplit::HalvedIgst(igst) => {
+                let half: Money = lines.iter().map(|amount| amount.percent_halved(igst)).sum();
+                (half, half, Money::ZERO)
+            }
+            TaxSplit::CgstSgst(cgst, sgst) => (tax_at(cgst), tax_at(sgst), Money::ZERO),
+        };
+        let interstate = matches!(split, TaxSplit::Igst(_));
+        for charge in at_own_rate {
+            let rate = charge.gst_rate.unwrap_or_default();
+            if interstate {
+                igst += charge.amount.percent(rate);
+            } else {
+                cgst += charge.amount.percent_halved(rate);
+                sgst += charge.amount.percent_halved(rate);
+            }
+        }
+        let taxable_value = self.taxable_value();
+        let cess: Money = self.items.iter().map(|item| item.cess_amount()).sum();
+
+        TaxSummary {
+            taxable_value,
+            cgst,
+            sgst,
+            igst,
+            cess,
+            total_tax: cgst + sgst + igst + cess,
+        }
+    }
+
+    /// Invoice total under `rules`, including the round-off needed to reach it.
+    pub fn totals(&self, rules: &RoundingRules) -> InvoiceTotals {
+        let tax = self.tax_summary(rules.tax_rounding);
+        let non_taxable_charges = self.non_taxable_charges();
+        let invoice_value = tax.taxable_value + non_taxable_charges + tax.total_tax;
+        let tcs = invoice_value.percent(self.tcs_percentage.unwrap_or_default());
+        let exact = invoice_value + tcs;
+        let total = match rules.round_off {
+            RoundOff::None => exact,
+            RoundOff::NearestRupee => exact.round_to_rupee(),
+            RoundOff::Up => exact.ceil_to_rupee(),
+            RoundOff::Down => exact.floor_to_rupee(),
+        };
+
+        InvoiceTotals {
+            discount: self.discount_total(),
+            taxable_value: tax.taxable_value,
+            non_taxable_charges,
+            interstate: self.is_interstate(),
+            cgst: tax.cgst,
+            sgst: tax.sgst,
+            igst: tax.igst,
+            cess: tax.cess,
+            total_tax: tax.total_tax,
+            tcs,
+            round_off: total - exact,
+            total,
+            tds_expected: tax.taxable_value.percent(self.tds_percentage.unwrap_or_default()),
+            total_inr: self.to_inr(total),
         }
     }
 
@@ -121,4 +790,4 @@ impl Invoice {
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,272 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+};
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An amount in whole paise. Stored as an INTEGER column and sent to the frontend as a
+/// rupee number (`1234.5`), so sums never drift by fractions of a paisa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_paise(paise: i64) -> Self {
+        Money(paise)
+    }
+
+    /// Rounds to the nearest paisa, halves away from zero.
+    pub fn from_rupees(rupees: f64) -> Self {
+        Money((rupees * 100.0).round() as i64)
+    }
+
+    pub fn paise(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_rupees(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Rate times a (possibly fractional) quantity, rounded to the paisa.
+    pub fn times(self, quantity: f64) -> Money {
+        Money((self.0 as f64 * quantity).round() as i64)
+    }
+
+    /// `rate` percent of the amount, rounded to the paisa with halves away from zero.
+    pub fn percent(self, rate: Percent) -> Money {
+        Money(divide_rounded(self.0 as i128 * rate.0 as i128, 10_000))
+    }
+
+    /// Half of `rate` percent, which is what each of CGST and SGST charge on an
+    /// intra-state supply taxed at `rate`.
+    pub fn percent_halved(self, rate: Percent) -> Money {
+        Money(divide_rounded(self.0 as i128 * rate.0 as i128, 20_000))
+    }
+
+    /// The amount rounded to a whole rupee, halves away from zero.
+    pub fn round_to_rupee(self) -> Money {
+        Money(divide_rounded(self.0 as i128, 100) * 100)
+    }
+
+    /// The next whole rupee at or above the amount.
+    pub fn ceil_to_rupee(self) -> Money {
+        Money(-(-self.0).div_euclid(100) * 100)
+    }
+
+    /// The whole rupee at or below the amount.
+    pub fn floor_to_rupee(self) -> Money {
+        Money(self.0.div_euclid(100) * 100)
+    }
+}
+
+/// `numerator / denominator` rounded half away from zero.
+fn divide_rounded(numerator: i128, denominator: i128) -> i64 {
+    let quotient = (numerator.abs() + denominator / 2) / denominator;
+    (quotient * numerator.signum()) as i64
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(f, "{}{}.{:02}", sign, self.0.abs() / 100, self.0.abs() % 100)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        Money(iter.map(|m| m.0).sum())
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        Money(iter.map(|m| m.0).sum())
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_rupees())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Money::from_rupees)
+    }
+}
+
+impl ToSql for Money {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.0.into())
+    }
+}
+
+impl FromSql for Money {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(paise) => Ok(Money(paise)),
+            // Aggregates like AVG come back as REAL even over paise columns
+            ValueRef::Real(paise) => Ok(Money(paise.round() as i64)),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A tax rate in hundredths of a percent (18% is 1800), exact for every GST slab
+/// including 0.25%. Sent to the frontend as a plain percentage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Percent(i64);
+
+impl Percent {
+    pub const ZERO: Percent = Percent(0);
+    pub const HUNDRED: Percent = Percent(10_000);
+
+    pub fn from_f64(percent: f64) -> Self {
+        Percent((percent * 100.0).round() as i64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+}
+
+impl Add for Percent {
+    type Output = Percent;
+    fn add(self, other: Percent) -> Percent {
+        Percent(self.0 + other.0)
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Serialize for Percent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Percent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Percent::from_f64)
+    }
+}
+
+impl ToSql for Percent {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.0.into())
+    }
+}
+
+impl FromSql for Percent {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_i64().map(Percent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rupees_round_half_paise_away_from_zero() {
+        let cases = [
+            (0.0, 0),
+            (0.005, 1),
+            (-0.005, -1),
+            (0.125, 13),
+            (-0.125, -13),
+            (0.124, 12),
+            (1234.5, 123450),
+        ];
+        for (rupees, paise) in cases {
+            assert_eq!(Money::from_rupees(rupees).paise(), paise, "{} rupees", rupees);
+        }
+    }
+
+    #[test]
+    fn tax_rounds_half_paise_away_from_zero() {
+        // (amount in paise, rate, full tax, each half of the split)
+        let cases = [
+            (1_000, 18.0, 180, 90),
+            (250, 18.0, 45, 23),
+            (-250, 18.0, -45, -23),
+            (1, 50.0, 1, 0),
+            (3, 50.0, 2, 1),
+            (10_050, 5.0, 503, 251),
+            (33_333, 0.25, 83, 42),
+        ];
+        for (amount, rate, full, half) in cases {
+            let amount = Money::from_paise(amount);
+            let rate = Percent::from_f64(rate);
+            assert_eq!(amount.percent(rate).paise(), full, "{} at {}%", amount, rate);
+            assert_eq!(amount.percent_halved(rate).paise(), half, "half of {} at {}%", amount, rate);
+        }
+    }
+
+    #[test]
+    fn rounds_to_whole_rupees() {
+        let cases = [(0, 0, 0, 0), (49, 0, 100, 0), (50, 100, 100, 0), (-50, -100, 0, -100), (199, 200, 200, 100)];
+        for (paise, nearest, up, down) in cases {
+            let amount = Money::from_paise(paise);
+            assert_eq!(amount.round_to_rupee().paise(), nearest, "{} paise to nearest", paise);
+            assert_eq!(amount.ceil_to_rupee().paise(), up, "{} paise up", paise);
+            assert_eq!(amount.floor_to_rupee().paise(), down, "{} paise down", paise);
+        }
+    }
+
+    #[test]
+    fn percent_keeps_hundredths() {
+        let cases = [(18.0, 1800), (0.25, 25), (0.125, 13), (12.344, 1234), (0.0, 0)];
+        for (percent, hundredths) in cases {
+            assert_eq!(Percent::from_f64(percent), Percent(hundredths), "{}%", percent);
+        }
+        assert_eq!(Percent::from_f64(2.5).to_f64(), 2.5);
+    }
+}
@@ -0,0 +1,97 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{invoice::InvoiceStatus, money::Money};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMode {
+    Cash,
+    Upi,
+    BankTransfer,
+    Cheque,
+    Card,
+    Other,
+}
+
+impl PaymentMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMode::Cash => "cash",
+            PaymentMode::Upi => "upi",
+            PaymentMode::BankTransfer => "bank_transfer",
+            PaymentMode::Cheque => "cheque",
+            PaymentMode::Card => "card",
+            PaymentMode::Other => "other",
+        }
+    }
+}
+
+impl ToSql for PaymentMode {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for PaymentMode {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "cash" => Ok(PaymentMode::Cash),
+            "upi" => Ok(PaymentMode::Upi),
+            "bank_transfer" => Ok(PaymentMode::BankTransfer),
+            "cheque" => Ok(PaymentMode::Cheque),
+            "card" => Ok(PaymentMode::Card),
+            "other" => Ok(PaymentMode::Other),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// The part of a payment settled against one invoice. TDS the client deducted on
+/// that invoice counts towards settling it even though it was never received.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentAllocation {
+    pub invoice_id: String,
+    pub amount: Money,
+    #[serde(default)]
+    pub tds_amount: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Payment {
+    pub id: Option<String>,
+    pub company_id: Option<String>,
+    pub payment_date: String,
+    pub amount: Money, // Amount actually received
+    pub mode: PaymentMode,
+    pub reference: Option<String>, // UTR, cheque number etc.
+    #[serde(default)]
+    pub tds_deducted: Money,
+    pub notes: Option<String>,
+    pub allocations: Vec<PaymentAllocation>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvoiceBalance {
+    pub invoice_id: String,
+    pub invoice_number: String,
+    pub invoice_date: String,
+    pub total: Money,
+    pub received: Money,
+    pub tds: Money,
+    pub tds_expected: Money, // What the client should deduct, going by the invoice
+    pub credited: Money, // Credit notes raised against the invoice
+    pub outstanding: Money,
+    pub status: InvoiceStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompanyOutstanding {
+    pub company_id: String,
+    pub total_invoiced: Money,
+    pub total_received: Money,
+    pub total_tds: Money,
+    pub total_credited: Money,
+    pub outstanding: Money,
+    pub invoices: Vec<InvoiceBalance>,
+}
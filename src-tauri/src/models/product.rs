@@ -0,0 +1,52 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::{Money, Percent};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductKind {
+    Goods,    // Classified under an HSN code
+    Services, // Classified under a SAC code
+}
+
+impl ProductKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductKind::Goods => "goods",
+            ProductKind::Services => "services",
+        }
+    }
+}
+
+impl ToSql for ProductKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ProductKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "goods" => Ok(ProductKind::Goods),
+            "services" => Ok(ProductKind::Services),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Product {
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub hsn_code: String, // HSN for goods, SAC for services
+    pub unit: String,     // Unit quantity code, e.g. NOS, KGS
+    pub default_rate: Money,
+    pub gst_rate: Percent,
+    pub kind: ProductKind,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub low_stock_threshold: Option<f64>, // Goods at or below this quantity are flagged
+}
@@ -0,0 +1,160 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    invoice::{AdditionalCharges, Discount, Invoice, InvoiceItem, TransportDetails},
+    money::{Money, Percent},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotationKind {
+    #[default]
+    Quotation,
+    Proforma,
+}
+
+impl QuotationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotationKind::Quotation => "quotation",
+            QuotationKind::Proforma => "proforma",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            QuotationKind::Quotation => "Quotation",
+            QuotationKind::Proforma => "Proforma Invoice",
+        }
+    }
+}
+
+impl ToSql for QuotationKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for QuotationKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "quotation" => Ok(QuotationKind::Quotation),
+            "proforma" => Ok(QuotationKind::Proforma),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotationStatus {
+    #[default]
+    Sent,
+    Accepted,
+    Rejected,
+    Expired,   // Set automatically once valid_until has passed
+    Converted, // A tax invoice has been raised from it
+}
+
+impl QuotationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotationStatus::Sent => "sent",
+            QuotationStatus::Accepted => "accepted",
+            QuotationStatus::Rejected => "rejected",
+            QuotationStatus::Expired => "expired",
+            QuotationStatus::Converted => "converted",
+        }
+    }
+}
+
+impl ToSql for QuotationStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for QuotationStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "sent" => Ok(QuotationStatus::Sent),
+            "accepted" => Ok(QuotationStatus::Accepted),
+            "rejected" => Ok(QuotationStatus::Rejected),
+            "expired" => Ok(QuotationStatus::Expired),
+            "converted" => Ok(QuotationStatus::Converted),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A quotation or proforma invoice. Uses the invoice item model but lives in its own
+/// table, so it never shows up in GST reports or moves stock until it is converted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quotation {
+    pub id: Option<String>,
+    #[serde(default)]
+    pub kind: QuotationKind,
+    pub quotation_number: String,
+    pub quotation_date: String,
+    pub valid_until: String,
+    #[serde(default)]
+    pub status: QuotationStatus,
+    pub company_id: Option<String>,
+    pub issuer_name: String,
+    pub issuer_address: String,
+    pub issuer_gst_number: String,
+    pub issuer_phone: String,
+    pub issuer_email: Option<String>,
+    pub recipient_name: String,
+    pub recipient_address: String,
+    pub recipient_gst_number: String,
+    pub recipient_phone: String,
+    pub recipient_email: Option<String>,
+    pub place_of_supply: String,
+    pub items: Vec<InvoiceItem>,
+    pub amount: Money,
+    pub cgst_percentage: Option<Percent>,
+    pub sgst_percentage: Option<Percent>,
+    pub igst_percentage: Option<Percent>,
+    pub additional_charges: Option<Vec<AdditionalCharges>>,
+    #[serde(default)]
+    pub discount: Option<Discount>,
+    pub total: Money,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub converted_invoice_id: Option<String>,
+}
+
+impl Quotation {
+    /// The quoted supply as an invoice to the same client, without a number or date.
+    pub fn to_invoice(&self) -> Invoice {
+        Invoice {
+            issuer_name: self.issuer_name.clone(),
+            issuer_address: self.issuer_address.clone(),
+            issuer_gst_number: self.issuer_gst_number.clone(),
+            issuer_phone: self.issuer_phone.clone(),
+            issuer_email: self.issuer_email.clone(),
+            recipient_name: self.recipient_name.clone(),
+            recipient_address: self.recipient_address.clone(),
+            recipient_gst_number: self.recipient_gst_number.clone(),
+            recipient_phone: self.recipient_phone.clone(),
+            recipient_email: self.recipient_email.clone(),
+            amount: self.amount,
+            cgst_percentage: self.cgst_percentage,
+            sgst_percentage: self.sgst_percentage,
+            igst_percentage: self.igst_percentage,
+            additional_charges: self.additional_charges.clone(),
+            discount: self.discount,
+            place_of_supply: Some(self.place_of_supply.clone()),
+            transport_details: TransportDetails {
+                place_of_supply: self.place_of_supply.clone(),
+                ..Default::default()
+            },
+            total: self.total,
+            items: self.items.clone(),
+            company_id: self.company_id.clone(),
+            ..Default::default()
+        }
+    }
+}
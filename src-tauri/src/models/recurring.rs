@@ -0,0 +1,96 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{invoice::Invoice, money::Money};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceFrequency {
+    #[default]
+    Monthly,
+    Quarterly,
+    HalfYearly,
+    Yearly,
+}
+
+impl RecurrenceFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecurrenceFrequency::Monthly => "monthly",
+            RecurrenceFrequency::Quarterly => "quarterly",
+            RecurrenceFrequency::HalfYearly => "half_yearly",
+            RecurrenceFrequency::Yearly => "yearly",
+        }
+    }
+
+    pub fn months(&self) -> u32 {
+        match self {
+            RecurrenceFrequency::Monthly => 1,
+            RecurrenceFrequency::Quarterly => 3,
+            RecurrenceFrequency::HalfYearly => 6,
+            RecurrenceFrequency::Yearly => 12,
+        }
+    }
+}
+
+impl ToSql for RecurrenceFrequency {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for RecurrenceFrequency {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "monthly" => Ok(RecurrenceFrequency::Monthly),
+            "quarterly" => Ok(RecurrenceFrequency::Quarterly),
+            "half_yearly" => Ok(RecurrenceFrequency::HalfYearly),
+            "yearly" => Ok(RecurrenceFrequency::Yearly),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A retainer billed on a fixed day every period. `template` supplies everything on the
+/// generated invoices except the number and date.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecurringSchedule {
+    pub id: Option<String>,
+    pub name: String,
+    pub company_id: String,
+    pub frequency: RecurrenceFrequency,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub day_of_month: u32, // 1-31, months without that day bill on their last day
+    #[serde(default)]
+    pub next_run_date: Option<String>, // Worked out from the dates above, read only
+    #[serde(default = "default_active")]
+    pub active: bool,
+    pub template: Invoice,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecurringRunLog {
+    pub id: String,
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub run_date: String, // Date the invoice was due to be raised on
+    pub invoice_id: Option<String>,
+    pub invoice_number: Option<String>,
+    pub error: Option<String>, // Set when the invoice could not be generated
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpcomingRun {
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub company_id: String,
+    pub recipient_name: String,
+    pub run_date: String,
+    pub total: Money,
+}
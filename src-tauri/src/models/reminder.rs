@@ -0,0 +1,83 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+/// When to remind clients about an unpaid invoice, counted from its due date (invoice
+/// date plus the client's payment terms). Templates take the invoice email placeholders
+/// along with `{due_date}`, `{days_overdue}` and `{outstanding}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReminderRule {
+    pub id: Option<String>,
+    pub name: String,
+    pub days_after_due: i32, // Negative to remind before the due date
+    #[serde(default)]
+    pub repeat_every_days: Option<u32>, // Keep reminding at this interval until paid
+    #[serde(default = "default_active")]
+    pub active: bool,
+    #[serde(default)]
+    pub subject_template: Option<String>,
+    #[serde(default)]
+    pub body_template: Option<String>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+pub const DEFAULT_REMINDER_SUBJECT_TEMPLATE: &str = "Payment reminder: {document_type} {invoice_number} due {due_date}";
+pub const DEFAULT_REMINDER_BODY_TEMPLATE: &str = "Dear {recipient_name},
+
+This is a reminder that {document_type} {invoice_number} dated {invoice_date} for {total} is due for payment on {due_date}. {outstanding} is outstanding.
+
+Please ignore this if you have already paid.
+
+Regards,
+{issuer_name}";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderStatus {
+    Sent,
+    Failed, // Tried again on the next run
+    Skipped, // Superseded by a later reminder for the same invoice in the same run
+}
+
+impl ReminderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderStatus::Sent => "sent",
+            ReminderStatus::Failed => "failed",
+            ReminderStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl ToSql for ReminderStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ReminderStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "sent" => Ok(ReminderStatus::Sent),
+            "failed" => Ok(ReminderStatus::Failed),
+            "skipped" => Ok(ReminderStatus::Skipped),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReminderLogEntry {
+    pub id: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub invoice_id: String,
+    pub invoice_number: String,
+    pub reminder_date: String, // The day the rule called for, which may be before the run
+    pub recipients: Vec<String>,
+    pub status: ReminderStatus,
+    pub error: Option<String>,
+    pub created_at: String,
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AgeingBasis {
+    #[default]
+    InvoiceDate,
+    DueDate, // invoice_date + the client's payment_terms_days
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AgeingRow {
+    pub company_id: Option<String>,
+    pub company_name: String,
+    pub days_0_30: Money, // Includes invoices that are not yet due
+    pub days_31_60: Money,
+    pub days_61_90: Money,
+    pub days_over_90: Money,
+    pub credit: Money, // Credit notes not set against an unpaid invoice, taken off the total
+    pub total: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgeingReport {
+    pub as_of: String,
+    pub basis: AgeingBasis,
+    pub rows: Vec<AgeingRow>,
+    pub totals: AgeingRow,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEntryKind {
+    Invoice,
+    DebitNote,
+    CreditNote,
+    Payment,
+    Tds, // TDS the client deducted while paying
+}
+
+impl LedgerEntryKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LedgerEntryKind::Invoice => "Invoice",
+            LedgerEntryKind::DebitNote => "Debit Note",
+            LedgerEntryKind::CreditNote => "Credit Note",
+            LedgerEntryKind::Payment => "Payment",
+            LedgerEntryKind::Tds => "TDS",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub date: String,
+    pub kind: LedgerEntryKind,
+    pub reference: String, // Document number, or the payment reference
+    pub debit: Money,
+    pub credit: Money,
+    pub balance: Money, // Running balance, positive when the client owes us
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClientLedger {
+    pub company_id: String,
+    pub company_name: String,
+    pub from_date: String,
+    pub to_date: String,
+    pub opening_balance: Money,
+    pub entries: Vec<LedgerEntry>,
+    pub total_debit: Money,
+    pub total_credit: Money,
+    pub closing_balance: Money,
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+
+/// A state or union territory with its GST state code, the first two digits of every
+/// GSTIN registered there.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct IndianState {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub union_territory: bool,
+}
+
+/// Place of supply for exports, which are outside India.
+pub const OTHER_COUNTRIES: &str = "96";
+
+pub const STATES: [IndianState; 40] = [
+    state("01", "Jammu and Kashmir", true),
+    state("02", "Himachal Pradesh", false),
+    state("03", "Punjab", false),
+    state("04", "Chandigarh", true),
+    state("05", "Uttarakhand", false),
+    state("06", "Haryana", false),
+    state("07", "Delhi", true),
+    state("08", "Rajasthan", false),
+    state("09", "Uttar Pradesh", false),
+    state("10", "Bihar", false),
+    state("11", "Sikkim", false),
+    state("12", "Arunachal Pradesh", false),
+    state("13", "Nagaland", false),
+    state("14", "Manipur", false),
+    state("15", "Mizoram", false),
+    state("16", "Tripura", false),
+    state("17", "Meghalaya", false),
+    state("18", "Assam", false),
+    state("19", "West Bengal", false),
+    state("20", "Jharkhand", false),
+    state("21", "Odisha", false),
+    state("22", "Chhattisgarh", false),
+    state("23", "Madhya Pradesh", false),
+    state("24", "Gujarat", false),
+    // 25 was merged into 26 in 2020, GSTINs issued before that still carry it
+    state("25", "Daman and Diu", true),
+    state("26", "Dadra and Nagar Haveli and Daman and Diu", true),
+    state("27", "Maharashtra", false),
+    state("28", "Andhra Pradesh (Before Division)", false),
+    state("29", "Karnataka", false),
+    state("30", "Goa", false),
+    state("31", "Lakshadweep", true),
+    state("32", "Kerala", false),
+    state("33", "Tamil Nadu", false),
+    state("34", "Puducherry", true),
+    state("35", "Andaman and Nicobar Islands", true),
+    state("36", "Telangana", false),
+    state("37", "Andhra Pradesh", false),
+    state("38", "Ladakh", true),
+    state("96", "Other Countries", false),
+    state("97", "Other Territory", true),
+];
+
+const fn state(code: &'static str, name: &'static str, union_territory: bool) -> IndianState {
+    IndianState { code, name, union_territory }
+}
+
+pub fn state_by_code(code: &str) -> Option<&'static IndianState> {
+    STATES.iter().find(|state| state.code == code)
+}
+
+/// Looks a place of supply up by its code (`"27"`, `"27-Maharashtra"`) or by name.
+pub fn find_state(value: &str) -> Option<&'static IndianState> {
+    let value = value.trim();
+    value
+        .get(0..2)
+        .filter(|code| code.chars().all(|c| c.is_ascii_digit()))
+        .and_then(state_by_code)
+        .or_else(|| STATES.iter().find(|state| state.name.eq_ignore_ascii_case(value)))
+}
+
+/// First three digits of a pincode (inclusive ranges) and the state they belong to.
+/// Checked in order, so the narrower ranges come first. A few sorting districts
+/// straddle a border (Puducherry inside 605, Lakshadweep inside 682, Daman and
+/// Silvassa inside 396); those resolve to the larger state and can be changed by hand.
+const PINCODE_PREFIXES: [(u32, u32, &str); 36] = [
+    (110, 110, "07"),
+    (121, 136, "06"),
+    (160, 160, "04"),
+    (140, 159, "03"),
+    (171, 177, "02"),
+    (194, 194, "38"),
+    (180, 193, "01"),
+    (246, 246, "05"),
+    (248, 249, "05"),
+    (263, 263, "05"),
+    (201, 285, "09"),
+    (301, 345, "08"),
+    (360, 396, "24"),
+    (403, 403, "30"),
+    (400, 445, "27"),
+    (450, 488, "23"),
+    (490, 497, "22"),
+    (500, 509, "36"),
+    (510, 535, "37"),
+    (560, 591, "29"),
+    (600, 643, "33"),
+    (670, 695, "32"),
+    (737, 737, "11"),
+    (744, 744, "35"),
+    (700, 743, "19"),
+    (750, 770, "21"),
+    (781, 788, "18"),
+    (790, 792, "12"),
+    (793, 794, "17"),
+    (795, 795, "14"),
+    (796, 796, "15"),
+    (797, 798, "13"),
+    (799, 799, "16"),
+    (814, 816, "20"),
+    (822, 835, "20"),
+    (800, 855, "10"),
+];
+
+/// The state a pincode is in, from the postal sorting district in its first three digits.
+pub fn state_for_pincode(pincode: &str) -> Option<&'static IndianState> {
+    let pincode = pincode.trim();
+    if pincode.len() != 6 || !pincode.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let prefix: u32 = pincode[0..3].parse().ok()?;
+
+    PINCODE_PREFIXES
+        .iter()
+        .find(|(from, to, _)| (*from..=*to).contains(&prefix))
+        .and_then(|(_, _, code)| state_by_code(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_states_by_code_and_name() {
+        let cases = [
+            ("27", Some("Maharashtra")),
+            ("07", Some("Delhi")),
+            ("27-Maharashtra", Some("Maharashtra")),
+            (" 29 ", Some("Karnataka")),
+            ("27AAAAA0000A1Z5", Some("Maharashtra")), // A GSTIN starts with its state code
+            ("tamil nadu", Some("Tamil Nadu")),
+            ("96", Some("Other Countries")),
+            ("97", Some("Other Territory")),
+            ("99", None), // Centre jurisdiction, never a place of supply
+            ("00", None),
+            ("7", None),
+            ("", None),
+            ("Atlantis", None),
+        ];
+        for (value, name) in cases {
+            assert_eq!(find_state(value).map(|state| state.name), name, "{:?}", value);
+        }
+        assert!(state_by_code("97").unwrap().union_territory);
+        assert!(!state_by_code("96").unwrap().union_territory);
+        assert_eq!(state_by_code("27-Maharashtra"), None);
+    }
+
+    #[test]
+    fn finds_states_by_pincode() {
+        let cases = [
+            ("110001", Some("07")),
+            ("160017", Some("04")), // Chandigarh, inside Punjab's range
+            ("194101", Some("38")), // Leh, inside Jammu and Kashmir's range
+            ("248001", Some("05")),
+            ("403001", Some("30")), // Goa, inside Maharashtra's range
+            ("411001", Some("27")),
+            (" 560001 ", Some("29")),
+            ("737101", Some("11")),
+            ("744101", Some("35")),
+            ("799001", Some("16")),
+            ("814112", Some("20")),
+            ("800001", Some("10")),
+            ("999999", None),
+            ("000000", None),
+            ("560", None),
+            ("4110011", None),
+            ("41100A", None),
+            ("", None),
+        ];
+        for (pincode, code) in cases {
+            assert_eq!(state_for_pincode(pincode).map(|state| state.code), code, "{:?}", pincode);
+        }
+    }
+}
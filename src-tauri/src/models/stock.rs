@@ -0,0 +1,89 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StockMovementKind {
+    Opening,
+    Purchase,
+    Adjustment,   // Manual correction, either direction
+    Sale,         // Issued on a tax invoice
+    SalesReturn,  // Received back on a credit note
+    Cancellation, // Sale reversed when its invoice is cancelled
+}
+
+impl StockMovementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StockMovementKind::Opening => "opening",
+            StockMovementKind::Purchase => "purchase",
+            StockMovementKind::Adjustment => "adjustment",
+            StockMovementKind::Sale => "sale",
+            StockMovementKind::SalesReturn => "sales_return",
+            StockMovementKind::Cancellation => "cancellation",
+        }
+    }
+}
+
+impl ToSql for StockMovementKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for StockMovementKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "opening" => Ok(StockMovementKind::Opening),
+            "purchase" => Ok(StockMovementKind::Purchase),
+            "adjustment" => Ok(StockMovementKind::Adjustment),
+            "sale" => Ok(StockMovementKind::Sale),
+            "sales_return" => Ok(StockMovementKind::SalesReturn),
+            "cancellation" => Ok(StockMovementKind::Cancellation),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A stock-in or stock-out. Quantities are signed: positive adds stock.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StockMovement {
+    pub id: Option<String>,
+    pub product_id: String,
+    pub movement_date: String,
+    pub kind: StockMovementKind,
+    pub quantity: f64,
+    pub reference: Option<String>, // Supplier bill or invoice number
+    pub invoice_id: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StockLevel {
+    pub product_id: String,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub low_stock_threshold: Option<f64>,
+    pub is_low: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StockLedgerLine {
+    pub movement: StockMovement,
+    pub balance: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StockMovementReport {
+    pub product_id: String,
+    pub name: String,
+    pub unit: String,
+    pub from_date: String,
+    pub to_date: String,
+    pub opening: f64,
+    pub inward: f64,
+    pub outward: f64,
+    pub closing: f64,
+    pub lines: Vec<StockLedgerLine>,
+}
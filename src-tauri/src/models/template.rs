@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::invoice::Invoice;
+
+/// A named invoice saved for a client to pre-fill the document dialog. The number and
+/// date on `invoice` are ignored; a fresh draft gets its own.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InvoiceTemplate {
+    pub id: Option<String>,
+    pub name: String,
+    pub company_id: String,
+    pub invoice: Invoice,
+}
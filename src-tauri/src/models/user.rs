@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Number;
 use sha2::digest::typenum::Integer;
 
+use crate::models::invoice::{RoundOff, TaxRounding};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
@@ -38,4 +40,10 @@ pub struct Profile {
     pub next_invoice_number: u32,
     pub next_debit_number: u32,
     pub next_credit_number: u32,
+    #[serde(default)]
+    pub tax_rounding: TaxRounding,
+    #[serde(default)]
+    pub round_off: RoundOff,
+    #[serde(default)]
+    pub upi_id: Option<String>, // Shown as a payment QR on invoices when set
 }
\ No newline at end of file
@@ -0,0 +1,151 @@
+//! Rupee amounts the way Indian invoices print them: lakh/crore digit grouping and the
+//! amount in words.
+
+use crate::models::money::Money;
+
+const ONES: [&str; 20] = [
+    "", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten",
+    "Eleven", "Twelve", "Thirteen", "Fourteen", "Fifteen", "Sixteen", "Seventeen", "Eighteen", "Nineteen",
+];
+const TENS: [&str; 10] = ["", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety"];
+
+/// Excel number format that groups digits in lakhs and crores. Excel takes at most two
+/// conditions, so it only covers positive amounts, see [`xlsx_indian_format`].
+pub const XLSX_INDIAN_FORMAT: &str = r"[>=10000000]##\,##\,##\,##0.00;[>=100000]##\,##\,##0.00;##,##0.00";
+
+/// Number format for one cell. Negative amounts get a plain two section format whose
+/// grouping is picked by size, as the conditions are used up by the positive ones.
+pub fn xlsx_indian_format(amount: f64) -> &'static str {
+    match -amount {
+        negative if negative >= 10_000_000.0 => r"##\,##\,##\,##0.00;-##\,##\,##\,##0.00",
+        negative if negative >= 100_000.0 => r"##\,##\,##0.00;-##\,##\,##0.00",
+        negative if negative > 0.0 => "##,##0.00;-##,##0.00",
+        _ => XLSX_INDIAN_FORMAT,
+    }
+}
+
+/// `1234567.5` -> `"Rupees Twelve Lakh Thirty Four Thousand Five Hundred Sixty Seven and Fifty Paise Only"`
+pub fn amount_in_words(amount: Money) -> String {
+    let total_paise = amount.paise().unsigned_abs();
+    let (rupees, paise) = (total_paise / 100, total_paise % 100);
+
+    let mut words = String::new();
+    if amount < Money::ZERO {
+        words.push_str("Minus ");
+    }
+    words.push_str("Rupees ");
+    words.push_str(&number_in_words(rupees));
+    if paise > 0 {
+        words.push_str(" and ");
+        words.push_str(&number_in_words(paise));
+        words.push_str(" Paise");
+    }
+    words.push_str(" Only");
+    words
+}
+
+/// Whole number in words using the Indian system (thousand, lakh, crore).
+pub fn number_in_words(number: u64) -> String {
+    if number == 0 {
+        return "Zero".to_string();
+    }
+
+    let scales: [(u64, &str); 4] = [(10_000_000, "Crore"), (100_000, "Lakh"), (1_000, "Thousand"), (100, "Hundred")];
+    for (size, name) in scales {
+        if number >= size {
+            let rest = number % size;
+            let mut words = format!("{} {}", number_in_words(number / size), name);
+            if rest > 0 {
+                words.push(' ');
+                words.push_str(&number_in_words(rest));
+            }
+            return words;
+        }
+    }
+
+    let number = number as usize;
+    match (number < 20, ONES[number % 10]) {
+        (true, _) => ONES[number].to_string(),
+        (false, "") => TENS[number / 10].to_string(),
+        (false, ones) => format!("{} {}", TENS[number / 10], ones),
+    }
+}
+
+/// Two decimals with Indian digit grouping: `1234567.5` -> `"12,34,567.50"`. Rounds to
+/// the paisa the way [`Money`] does.
+pub fn format_indian(amount: f64) -> String {
+    let paise = Money::from_rupees(amount).paise();
+    let whole = (paise.unsigned_abs() / 100).to_string();
+    let fraction = paise.unsigned_abs() % 100;
+
+    let mut grouped = String::new();
+    let split = whole.len().saturating_sub(3);
+    let (head, last_three) = whole.split_at(split);
+    for (index, digit) in head.chars().enumerate() {
+        if index > 0 && (head.len() - index) % 2 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if !head.is_empty() {
+        grouped.push(',');
+    }
+    grouped.push_str(last_three);
+
+    let sign = if paise < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, grouped, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_in_words_uses_lakh_and_crore() {
+        let cases = [
+            (0.0, "Rupees Zero Only"),
+            (0.05, "Rupees Zero and Five Paise Only"),
+            (0.99, "Rupees Zero and Ninety Nine Paise Only"),
+            (100000.0, "Rupees One Lakh Only"),
+            (10000000.0, "Rupees One Crore Only"),
+            (123456789.5, "Rupees Twelve Crore Thirty Four Lakh Fifty Six Thousand Seven Hundred Eighty Nine and Fifty Paise Only"),
+            (-1180.0, "Minus Rupees One Thousand One Hundred Eighty Only"),
+            (-0.01, "Minus Rupees Zero and One Paise Only"),
+            (10.995, "Rupees Eleven Only"),
+        ];
+        for (rupees, words) in cases {
+            assert_eq!(amount_in_words(Money::from_rupees(rupees)), words, "{}", rupees);
+        }
+    }
+
+    #[test]
+    fn format_indian_groups_in_lakhs_and_crores() {
+        let cases = [
+            (0.0, "0.00"),
+            (0.05, "0.05"),
+            (999.0, "999.00"),
+            (1000.0, "1,000.00"),
+            (100000.0, "1,00,000.00"),
+            (10000000.0, "1,00,00,000.00"),
+            (1234567.5, "12,34,567.50"),
+            (-100000.0, "-1,00,000.00"),
+            (-0.004, "0.00"),
+            (0.995, "1.00"),
+            (1.995, "2.00"),
+            (99999.995, "1,00,000.00"),
+            (-2.675, "-2.68"),
+        ];
+        for (amount, formatted) in cases {
+            assert_eq!(format_indian(amount), formatted, "{}", amount);
+        }
+    }
+
+    #[test]
+    fn xlsx_format_keeps_indian_grouping_for_negatives() {
+        assert_eq!(xlsx_indian_format(12345678.0), XLSX_INDIAN_FORMAT);
+        assert_eq!(xlsx_indian_format(0.0), XLSX_INDIAN_FORMAT);
+        assert_eq!(xlsx_indian_format(-500.0), "##,##0.00;-##,##0.00");
+        assert_eq!(xlsx_indian_format(-100000.0), r"##\,##\,##0.00;-##\,##\,##0.00");
+        assert_eq!(xlsx_indian_format(-12345678.0), r"##\,##\,##\,##0.00;-##\,##\,##\,##0.00");
+    }
+}
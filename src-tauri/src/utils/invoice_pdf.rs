@@ -0,0 +1,128 @@
+//! The invoice as an A4 PDF, for attaching to emails.
+
+use crate::{
+    models::invoice::{Invoice, InvoiceTotals, SupplyType},
+    utils::{
+        amount::amount_in_words,
+        pdf::{Orientation, PdfWriter},
+        table::{format_date, Cell, Table},
+        upi::qr_modules,
+    },
+};
+
+const ITEM_HEADERS: [&str; 7] = ["Description", "HSN/SAC", "Qty", "Unit", "Rate", "Discount", "Amount"];
+
+/// QR code size on the page, in mm. Large enough for phone cameras at arm's length.
+const UPI_QR_SIZE: f32 = 32.0;
+
+/// Renders the invoice, with a UPI payment QR under the bank details when `upi_uri` is given.
+pub fn invoice_pdf(invoice: &Invoice, totals: &InvoiceTotals, upi_uri: Option<&str>) -> Result<Vec<u8>, String> {
+    let title = format!("{} {}", invoice.document_type.label(), invoice.invoice_number);
+    let mut pdf = PdfWriter::new(&title, Orientation::Portrait)?;
+
+    pdf.heading(invoice.document_type.label());
+    pdf.bold_text(&invoice.issuer_name);
+    pdf.text(&invoice.issuer_address);
+    pdf.text(&format!("GSTIN: {}", invoice.issuer_gst_number));
+    pdf.divider();
+
+    pdf.text(&format!("Invoice No: {}", invoice.invoice_number));
+    pdf.text(&format!("Invoice Date: {}", format_date(&invoice.invoice_date)));
+    if let Some(state) = invoice.place_of_supply_state() {
+        pdf.text(&format!("Place of Supply: {} ({})", state.name, state.code));
+    }
+    if invoice.supply_type != SupplyType::Regular {
+        let lut = invoice.lut_reference.as_deref().map(|lut| format!(", LUT/Bond {}", lut)).unwrap_or_default();
+        pdf.text(&format!("Supply: {}{}", invoice.supply_type.label(), lut));
+    }
+    if let Some(currency) = &invoice.foreign_currency {
+        pdf.text(&format!("Currency: {} (1 {} = Rs. {})", currency.code, currency.code, currency.exchange_rate));
+    }
+    if invoice.reverse_charge {
+        pdf.text("Tax payable on reverse charge: Yes");
+    }
+    pdf.space(3.0);
+
+    pdf.bold_text("Billed to");
+    pdf.text(&invoice.recipient_name);
+    pdf.text(&invoice.recipient_address);
+    pdf.text(&format!("GSTIN: {}", invoice.recipient_gst_number));
+    if let Some(ship_to) = &invoice.ship_to {
+        pdf.space(2.0);
+        pdf.bold_text("Shipped to");
+        pdf.text(&ship_to.name);
+        pdf.text(&ship_to.address.to_string());
+        if let Some(gst_number) = &ship_to.gst_number {
+            pdf.text(&format!("GSTIN: {}", gst_number));
+        }
+    }
+    pdf.space(3.0);
+
+    let mut items = Table::new("Items", &ITEM_HEADERS);
+    for item in &invoice.items {
+        items.push(vec![
+            item.description.as_str().into(),
+            item.hsn_code.as_str().into(),
+            item.quantity.into(),
+            item.unit.as_str().into(),
+            item.rate.into(),
+            item.discount_amount().into(),
+            item.net_amount().into(),
+        ]);
+    }
+    for charge in invoice.additional_charges.iter().flatten() {
+        items.push(vec![
+            charge.description.as_str().into(),
+            charge.hsn_code.as_deref().unwrap_or_default().into(),
+            "".into(),
+            "".into(),
+            "".into(),
+            "".into(),
+            charge.amount.into(),
+        ]);
+    }
+    pdf.table(&items);
+
+    let mut summary = Table::new("Summary", &["", "Amount"]);
+    let lines = [
+        ("Discount", totals.discount),
+        ("Taxable Value", totals.taxable_value),
+        ("Non-taxable Charges", totals.non_taxable_charges),
+        ("CGST", totals.cgst),
+        ("SGST", totals.sgst),
+        ("IGST", totals.igst),
+        ("Cess", totals.cess),
+        ("TCS", totals.tcs),
+        ("Round Off", totals.round_off),
+    ];
+    for (label, amount) in lines {
+        // Taxable value is always shown, the rest only when they apply
+        if !amount.is_zero() || label == "Taxable Value" {
+            summary.push(vec![label.into(), amount.into()]);
+        }
+    }
+    summary.totals = Some(vec![Cell::from("Total"), totals.total.into()]);
+    pdf.table(&summary);
+
+    match &invoice.foreign_currency {
+        Some(_) => pdf.text(&format!("Rupee equivalent: {}", amount_in_words(totals.total_inr))),
+        None => pdf.text(&amount_in_words(totals.total_inr)),
+    }
+    pdf.space(3.0);
+
+    let bank = &invoice.bank_details;
+    pdf.bold_text("Bank Details");
+    pdf.text(&format!("{}, {}", bank.bank_name, bank.branch));
+    pdf.text(&format!("A/c {} ({}), IFSC {}", bank.account_no, bank.account_name, bank.ifsc_code));
+
+    if let Some(uri) = upi_uri {
+        let (modules, columns) = qr_modules(uri)?;
+        pdf.space(3.0);
+        pdf.ensure_space(UPI_QR_SIZE + 6.0);
+        pdf.bold_text("Scan to pay with any UPI app");
+        pdf.space(1.0);
+        pdf.module_grid(&modules, columns, UPI_QR_SIZE);
+    }
+
+    pdf.to_bytes()
+}
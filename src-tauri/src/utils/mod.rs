@@ -1,3 +1,9 @@
+pub mod amount;
+pub mod invoice_pdf;
+pub mod pdf;
+pub mod table;
+pub mod upi;
+
 use once_cell::sync::OnceCell;
 use std::fs;
 use std::path::PathBuf;
@@ -0,0 +1,235 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use printpdf::{
+    path::PaintMode, BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Rect,
+};
+
+use crate::utils::table::{Cell, Table};
+
+const MARGIN: f32 = 12.0;
+const PT_TO_MM: f32 = 0.3528;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Thin layer over printpdf that keeps a top-down cursor and starts new pages as
+/// content runs past the bottom margin. Coordinates are in mm from the bottom left.
+pub struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    width: f32,
+    height: f32,
+    cursor: f32,
+}
+
+impl PdfWriter {
+    pub fn new(title: &str, orientation: Orientation) -> Result<Self, String> {
+        let (width, height) = match orientation {
+            Orientation::Portrait => (210.0, 297.0),
+            Orientation::Landscape => (297.0, 210.0),
+        };
+        let (doc, page, layer) = PdfDocument::new(title, Mm(width), Mm(height), "Layer 1");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            layer,
+            regular,
+            bold,
+            width,
+            height,
+            cursor: height - MARGIN,
+        })
+    }
+
+    pub fn content_width(&self) -> f32 {
+        self.width - 2.0 * MARGIN
+    }
+
+    pub fn left(&self) -> f32 {
+        MARGIN
+    }
+
+    /// Current vertical position, moving down the page as content is added.
+    pub fn cursor(&self) -> f32 {
+        self.cursor
+    }
+
+    pub fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(self.width), Mm(self.height), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.cursor = self.height - MARGIN;
+    }
+
+    /// Starts a new page unless `needed` mm still fit above the bottom margin.
+    pub fn ensure_space(&mut self, needed: f32) {
+        if self.cursor - needed < MARGIN {
+            self.new_page();
+        }
+    }
+
+    pub fn space(&mut self, mm: f32) {
+        self.cursor -= mm;
+    }
+
+    pub fn heading(&mut self, text: &str) {
+        self.line_of_text(text, 14.0, true);
+        self.space(2.0);
+    }
+
+    pub fn text(&mut self, text: &str) {
+        self.line_of_text(text, 9.0, false);
+    }
+
+    pub fn bold_text(&mut self, text: &str) {
+        self.line_of_text(text, 9.0, true);
+    }
+
+    fn line_of_text(&mut self, text: &str, size: f32, bold: bool) {
+        let line_height = size * PT_TO_MM * 1.4;
+        self.ensure_space(line_height);
+        self.cursor -= line_height;
+        self.text_at(text, size, self.left(), self.cursor, bold, Align::Left, self.content_width());
+    }
+
+    /// Writes `text` in a box starting at `x` that is `width` mm wide, trimming it to fit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn text_at(&self, text: &str, size: f32, x: f32, y: f32, bold: bool, align: Align, width: f32) {
+        let text = fit_text(text, size, width);
+        let x = match align {
+            Align::Left => x,
+            Align::Right => x + width - text_width(&text, size),
+        };
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(y), font);
+    }
+
+    pub fn rule(&self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![(Point::new(Mm(x1), Mm(y1)), false), (Point::new(Mm(x2), Mm(y2)), false)],
+            is_closed: false,
+        });
+    }
+
+    /// Horizontal rule across the content width at the cursor.
+    pub fn divider(&mut self) {
+        self.space(1.5);
+        self.rule(self.left(), self.cursor, self.left() + self.content_width(), self.cursor);
+        self.space(1.5);
+    }
+
+    pub fn filled_rect(&self, x: f32, y: f32, width: f32, height: f32) {
+        self.layer
+            .add_rect(Rect::new(Mm(x), Mm(y), Mm(x + width), Mm(y + height)).with_mode(PaintMode::Fill));
+    }
+
+    /// Draws a square grid of `columns` modules per row, `size` mm across, at the cursor.
+    /// Dark (`true`) modules are filled in, as for a QR code.
+    pub fn module_grid(&mut self, modules: &[bool], columns: usize, size: f32) {
+        self.ensure_space(size);
+        let module = size / columns as f32;
+        for (index, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+            let (row, column) = (index / columns, index % columns);
+            self.filled_rect(self.left() + column as f32 * module, self.cursor - (row + 1) as f32 * module, module, module);
+        }
+        self.space(size);
+    }
+
+    /// Lays out a table across the content width, repeating the header on every page.
+    /// Numbers are right aligned and the totals row, if any, is bold.
+    pub fn table(&mut self, table: &Table) {
+        let columns = table.headers.len().max(1);
+        let size = if columns > 10 { 6.0 } else if columns > 6 { 7.5 } else { 9.0 };
+        let row_height = size * PT_TO_MM * 1.6;
+        let column_width = self.content_width() / columns as f32;
+
+        self.write_table_header(table, size, row_height, column_width);
+
+        let rows = table.rows.iter().map(|row| (row, false));
+        for (row, bold) in rows.chain(table.totals.iter().map(|row| (row, true))) {
+            if self.cursor - row_height < MARGIN {
+                self.new_page();
+                self.write_table_header(table, size, row_height, column_width);
+            }
+            self.cursor -= row_height;
+
+            for (col, cell) in row.iter().enumerate() {
+                let align = match cell {
+                    Cell::Number(_) => Align::Right,
+                    _ => Align::Left,
+                };
+                let x = self.left() + col as f32 * column_width;
+                self.text_at(&cell.to_display(), size, x + 1.0, self.cursor + 1.0, bold, align, column_width - 2.0);
+            }
+        }
+        self.divider();
+    }
+
+    fn write_table_header(&mut self, table: &Table, size: f32, row_height: f32, column_width: f32) {
+        self.divider();
+        self.cursor -= row_height;
+        for (col, header) in table.headers.iter().enumerate() {
+            let x = self.left() + col as f32 * column_width;
+            self.text_at(header, size, x + 1.0, self.cursor + 1.0, true, Align::Left, column_width - 2.0);
+        }
+        self.divider();
+    }
+
+    pub fn save(self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        self.doc
+            .save(&mut BufWriter::new(file))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn to_bytes(self) -> Result<Vec<u8>, String> {
+        self.doc.save_to_bytes().map_err(|e| e.to_string())
+    }
+}
+
+/// Builtin fonts carry no metrics we can query, so widths use Helvetica's average glyph width.
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * PT_TO_MM * 0.52
+}
+
+fn fit_text(text: &str, size: f32, width: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let max_chars = (width / (size * PT_TO_MM * 0.52)) as usize;
+    let mut fitted: String = text.chars().take(max_chars.saturating_sub(2)).collect();
+    fitted.push_str("..");
+    fitted
+}
+
+/// Renders a titled report with one table per section.
+pub fn write_tables_pdf(title: &str, subtitle: &[String], tables: &[Table], path: &Path) -> Result<(), String> {
+    let mut pdf = PdfWriter::new(title, Orientation::Landscape)?;
+    pdf.heading(title);
+    for line in subtitle {
+        pdf.text(line);
+    }
+
+    for table in tables {
+        pdf.space(4.0);
+        pdf.bold_text(&table.name);
+        pdf.table(table);
+    }
+
+    pdf.save(path)
+}
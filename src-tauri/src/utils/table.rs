@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use serde::Deserialize;
+
+use crate::models::money::{Money, Percent};
+use crate::utils::{
+    amount::{format_indian, xlsx_indian_format},
+    pdf::write_tables_pdf,
+};
+
+/// Dates are written as DD-MM-YYYY everywhere we hand data to an accountant.
+pub const EXPORT_DATE_FORMAT: &str = "%d-%m-%Y";
+
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    /// An ISO `YYYY-MM-DD` date as stored in the database.
+    Date(String),
+}
+
+impl Cell {
+    /// Plain text rendering used by CSV: numbers get two decimals, dates DD-MM-YYYY.
+    /// Numbers stay ungrouped so spreadsheets still read them as numbers.
+    pub fn to_text(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => format!("{:.2}", number),
+            Cell::Date(date) => format_date(date),
+        }
+    }
+
+    /// Rendering for printed documents: like `to_text` but numbers grouped in lakhs and crores.
+    pub fn to_display(&self) -> String {
+        match self {
+            Cell::Number(number) => format_indian(*number),
+            _ => self.to_text(),
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<Money> for Cell {
+    fn from(value: Money) -> Self {
+        Cell::Number(value.to_rupees())
+    }
+}
+
+impl From<Percent> for Cell {
+    fn from(value: Percent) -> Self {
+        Cell::Number(value.to_f64())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Pdf,
+}
+
+/// A named sheet of rows; the unit every exporter writes out.
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+    pub totals: Option<Vec<Cell>>, // Written after the rows, bold where the format allows
+}
+
+impl Table {
+    pub fn new(name: &str, headers: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            totals: None,
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    pub fn write_csv(&self, path: &Path) -> Result<(), String> {
+        let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+        writer.write_record(&self.headers).map_err(|e| e.to_string())?;
+        for row in self.rows.iter().chain(self.totals.iter()) {
+            writer
+                .write_record(row.iter().map(Cell::to_text))
+                .map_err(|e| e.to_string())?;
+        }
+        writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// Writes `tables` in the requested format and returns the paths written. XLSX and PDF
+/// hold every table in one file; CSV writes each table after the first next to `path`
+/// as `<name>_<table>.csv`.
+pub fn write_tables(title: &str, subtitle: &[String], tables: &[Table], format: ExportFormat, path: &Path) -> Result<Vec<String>, String> {
+    match format {
+        ExportFormat::Xlsx => write_xlsx(tables, path)?,
+        ExportFormat::Pdf => write_tables_pdf(title, subtitle, tables, path)?,
+        ExportFormat::Csv => {
+            let mut written = Vec::new();
+            for (index, table) in tables.iter().enumerate() {
+                let table_path = if index == 0 {
+                    path.to_path_buf()
+                } else {
+                    sibling_path(path, &table.name.to_lowercase().replace(' ', "_"))
+                };
+                table.write_csv(&table_path)?;
+                written.push(table_path.to_string_lossy().to_string());
+            }
+            return Ok(written);
+        }
+    }
+    Ok(vec![path.to_string_lossy().to_string()])
+}
+
+/// `exports/invoices.csv` + `items` -> `exports/invoices_items.csv`
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "export".to_string());
+    path.with_file_name(format!("{}_{}.csv", stem, suffix))
+}
+
+/// Writes every table as its own worksheet in a single workbook.
+pub fn write_xlsx(tables: &[Table], path: &Path) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("dd-mm-yyyy");
+
+    for table in tables {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(&table.name).map_err(|e| e.to_string())?;
+
+        for (col, header) in table.headers.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, header, &header_format)
+                .map_err(|e| e.to_string())?;
+        }
+
+        let rows = table.rows.iter().map(|row| (row, false));
+        for (index, (row, is_total)) in rows.chain(table.totals.iter().map(|row| (row, true))).enumerate() {
+            let row_no = index as u32 + 1;
+            for (col, cell) in row.iter().enumerate() {
+                let col = col as u16;
+                match cell {
+                    Cell::Text(text) if is_total => sheet
+                        .write_string_with_format(row_no, col, text, &header_format)
+                        .map(|_| ()),
+                    Cell::Text(text) => sheet.write_string(row_no, col, text).map(|_| ()),
+                    Cell::Number(number) if is_total => sheet
+                        .write_number_with_format(row_no, col, *number, &Format::new().set_bold().set_num_format(xlsx_indian_format(*number)))
+                        .map(|_| ()),
+                    Cell::Number(number) => sheet
+                        .write_number_with_format(row_no, col, *number, &Format::new().set_num_format(xlsx_indian_format(*number)))
+                        .map(|_| ()),
+                    Cell::Date(date) => match ExcelDateTime::parse_from_str(date) {
+                        Ok(date) => sheet
+                            .write_datetime_with_format(row_no, col, &date, &date_format)
+                            .map(|_| ()),
+                        Err(_) => sheet.write_string(row_no, col, date).map(|_| ()),
+                    },
+                }
+                .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    workbook.save(path).map_err(|e| e.to_string())
+}
+
+/// Converts a stored `YYYY-MM-DD` date to DD-MM-YYYY, leaving anything unparseable as-is.
+pub fn format_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format(EXPORT_DATE_FORMAT).to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
@@ -0,0 +1,89 @@
+//! UPI payment links and the QR codes payment apps scan them from.
+
+use qrcode::{render::svg, types::Color, EcLevel, QrCode};
+use serde::Deserialize;
+
+use crate::models::money::Money;
+
+/// Pixels per QR module in the PNG and SVG images.
+const MODULE_PIXELS: u32 = 8;
+/// Light modules kept around the code, as scanners expect.
+const QUIET_ZONE: usize = 4;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    Png,
+    Svg,
+}
+
+/// `upi://pay` link for `amount` to `upi_id`, with `note` shown to the payer.
+pub fn upi_uri(upi_id: &str, payee_name: &str, amount: Money, note: &str) -> String {
+    // The UPI ID is checked to be URI safe, some apps don't decode an escaped `@`
+    format!(
+        "upi://pay?pa={}&pn={}&am={:.2}&cu=INR&tn={}",
+        upi_id.trim(),
+        urlencoding::encode(payee_name.trim()),
+        amount.to_rupees(),
+        urlencoding::encode(note)
+    )
+}
+
+/// A virtual payment address such as `shop@okhdfcbank`.
+pub fn is_upi_id(value: &str) -> bool {
+    match value.trim().split_once('@') {
+        Some((handle, provider)) => {
+            (2..=256).contains(&handle.len())
+                && handle.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+                && (2..=64).contains(&provider.len())
+                && provider.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+/// The QR code as rows of dark (`true`) and light modules, quiet zone included, and its width.
+pub fn qr_modules(data: &str) -> Result<(Vec<bool>, usize), String> {
+    let code = qr_code(data)?;
+    let width = code.width();
+    let size = width + 2 * QUIET_ZONE;
+    let colors = code.to_colors();
+
+    let mut modules = vec![false; size * size];
+    for y in 0..width {
+        for x in 0..width {
+            modules[(y + QUIET_ZONE) * size + x + QUIET_ZONE] = colors[y * width + x] == Color::Dark;
+        }
+    }
+    Ok((modules, size))
+}
+
+pub fn qr_png(data: &str) -> Result<Vec<u8>, String> {
+    let (modules, size) = qr_modules(data)?;
+    let scale = MODULE_PIXELS as usize;
+    let pixels: Vec<u8> = (0..size * scale)
+        .flat_map(|y| (0..size * scale).map(move |x| (y / scale, x / scale)))
+        .map(|(row, column)| if modules[row * size + column] { 0 } else { 255 })
+        .collect();
+
+    let mut png = Vec::new();
+    let side = (size * scale) as u32;
+    let mut encoder = png::Encoder::new(&mut png, side, side);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+pub fn qr_svg(data: &str) -> Result<String, String> {
+    Ok(qr_code(data)?
+        .render::<svg::Color>()
+        .module_dimensions(MODULE_PIXELS, MODULE_PIXELS)
+        .build())
+}
+
+fn qr_code(data: &str) -> Result<QrCode, String> {
+    QrCode::with_error_correction_level(data, EcLevel::M).map_err(|e| e.to_string())
+}
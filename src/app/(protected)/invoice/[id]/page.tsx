@@ -108,6 +108,24 @@ export default function InvoicePage() {
         })
 
         console.log(result);
+        const totals = await invoke<{
+          discount: number
+          taxable_value: number
+          non_taxable_charges: number
+          interstate: boolean
+          cgst: number
+          sgst: number
+          igst: number
+          cess: number
+        }>("calculate_invoice_totals", { invoice: result[0] })
+        const states = await invoke<{ code: string; name: string }[]>("get_states")
+        const placeOfSupply = result[0].place_of_supply ?? result[0].transport_details.place_of_supply
+        const state = states.find((s) => s.code === placeOfSupply)
+        const shipTo = result[0].ship_to
+        const formatAddress = (address: { line1: string; line2?: string; city: string; district?: string; state_code: string; pincode: string }) =>
+          [address.line1, address.line2, address.city, address.district, states.find((s) => s.code === address.state_code)?.name]
+            .filter(Boolean)
+            .join(", ") + ` - ${address.pincode}`
         // Your backend might return an array, even when searching by ID
         // if (result && result.length > 0) {
         //   setInvoiceData(result[0]) // Update the state with the fetched data
@@ -131,12 +149,26 @@ export default function InvoicePage() {
             gstNumber: result[0].recipient_gst_number,
             email: result[0].recipient_email,
           },
+          shipTo: shipTo
+            ? { name: shipTo.name, address: formatAddress(shipTo.address), gstNumber: shipTo.gst_number ?? "" }
+            : undefined,
           items: result[0].items,
           additionalCharges: result[0].additional_charges,
-          taxType: result[0].cgst_percentage === 0 ? "interstate" : "intrastate",
+          taxType: totals.interstate ? "interstate" : "intrastate",
           cgstRate: result[0].cgst_percentage,
           sgstRate: result[0].sgst_percentage,
           igstRate: result[0].igst_percentage,
+          discount: totals.discount,
+          taxableValue: totals.taxable_value,
+          nonTaxableCharges: totals.non_taxable_charges,
+          cgst: totals.cgst,
+          sgst: totals.sgst,
+          igst: totals.igst,
+          cess: totals.cess,
+          tcsRate: result[0].tcs_percentage,
+          tcs: result[0].tcs_amount,
+          roundOff: result[0].round_off,
+          total: result[0].total,
           notes: result[0]?.notes,
           transport: {
             name: result[0].transport_details.transporter_name,
@@ -144,7 +176,11 @@ export default function InvoicePage() {
             station: result[0].transport_details.station,
             eWayBillNo: result[0].transport_details.eway_bill_no,
           },
-          placeOfSupply: result[0].transport_details.place_of_supply,
+          placeOfSupply: state ? `${state.name} (${state.code})` : placeOfSupply,
+          supplyType: result[0].supply_type,
+          lutReference: result[0].lut_reference,
+          currency: result[0].foreign_currency,
+          totalInr: result[0].total_inr,
           reverseCharge: result[0].reverse_charge ? "Y" : "N",
           irn: result[0]?.irn,
           ackNo: result[0]?.ack_no,
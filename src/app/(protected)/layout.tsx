@@ -5,6 +5,14 @@ import { useEffect, useState } from "react";
 import { useRouter } from "next/navigation";
 import { Loader2 } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+interface ReminderLogEntry {
+  invoice_number: string;
+  rule_name: string;
+  status: "sent" | "failed" | "skipped";
+  error: string | null;
+}
 
 export default function ProtectedLayout({ children }: { children: React.ReactNode }) {
   const router = useRouter();
@@ -35,6 +43,18 @@ export default function ProtectedLayout({ children }: { children: React.ReactNod
     checkAuth();
   }, [router]);
 
+  useEffect(() => {
+    // Payment reminders are sent in the background after start-up and login
+    const unlisten = listen<ReminderLogEntry[]>("payment-reminders-run", ({ payload }) => {
+      for (const entry of payload.filter((entry) => entry.status === "failed")) {
+        console.warn(`Reminder "${entry.rule_name}" for ${entry.invoice_number} was not sent: ${entry.error}`);
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   // While checking the auth status, show a loading screen
   if (isAuthorized === null) {
     return (
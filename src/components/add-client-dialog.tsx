@@ -1,7 +1,7 @@
 "use client"
 
 import type React from "react"
-import { useState } from "react"
+import { useEffect, useState } from "react"
 import { Button } from "@/components/ui/button"
 import {
   Dialog,
@@ -13,10 +13,29 @@ import {
 } from "@/components/ui/dialog"
 import { Input } from "@/components/ui/input"
 import { Label } from "@/components/ui/label"
-import { Textarea } from "@/components/ui/textarea"
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select"
 import type { Client } from "@/components/client-management.tsx"
 import { invoke } from "@tauri-apps/api/core"
 
+interface IndianState {
+  code: string
+  name: string
+}
+
+const EMPTY_FORM = {
+  companyName: "",
+  ownerName: "",
+  gstNumber: "",
+  line1: "",
+  line2: "",
+  city: "",
+  district: "",
+  stateCode: "",
+  pincode: "",
+  phone: "",
+  email: "",
+}
+
 interface AddClientDialogProps {
   open: boolean
   onOpenChange: (open: boolean) => void
@@ -24,17 +43,27 @@ interface AddClientDialogProps {
 }
 
 export function AddClientDialog({ open, onOpenChange, onAddClient }: AddClientDialogProps) {
-  const [formData, setFormData] = useState({
-    companyName: "",
-    ownerName: "",
-    gstNumber: "",
-    address: "",
-    pincode: "",
-    phone: "",
-    email: "",
-  })
+  const [formData, setFormData] = useState(EMPTY_FORM)
 
   const [errors, setErrors] = useState<Record<string, string>>({})
+  const [indianStates, setIndianStates] = useState<IndianState[]>([])
+
+  useEffect(() => {
+    invoke<IndianState[]>("get_states").then(setIndianStates)
+  }, [])
+
+  // Fill the state in from the pincode, it can still be changed by hand
+  const handlePincodeChange = async (value: string) => {
+    handleInputChange("pincode", value)
+    if (/^[1-9][0-9]{5}$/.test(value)) {
+      try {
+        const state = await invoke<IndianState>("lookup_pincode", { pincode: value })
+        setFormData((current) => ({ ...current, pincode: value, stateCode: state.code }))
+      } catch {
+        // Unknown pincode, leave the state as it is
+      }
+    }
+  }
 
   const handleInputChange = (field: string, value: string) => {
     setFormData({ ...formData, [field]: value })
@@ -50,7 +79,9 @@ export function AddClientDialog({ open, onOpenChange, onAddClient }: AddClientDi
     if (!formData.companyName.trim()) newErrors.companyName = "Company name is required"
     if (!formData.ownerName.trim()) newErrors.ownerName = "Owner name is required"
     if (!formData.gstNumber.trim()) newErrors.gstNumber = "GST number is required"
-    if (!formData.address.trim()) newErrors.address = "Address is required"
+    if (!formData.line1.trim()) newErrors.line1 = "Address is required"
+    if (!formData.city.trim()) newErrors.city = "City is required"
+    if (!formData.stateCode) newErrors.stateCode = "State is required"
     if (!formData.pincode.trim()) newErrors.pincode = "Pincode is required"
     if (!formData.phone.trim()) newErrors.phone = "Phone number is required"
 
@@ -87,39 +118,31 @@ export function AddClientDialog({ open, onOpenChange, onAddClient }: AddClientDi
           name: formData.companyName,
           owner_name: formData.ownerName,
           gst_number: formData.gstNumber,
-          address: formData.address,
+          address: "",
           pincode: formData.pincode,
+          address_details: {
+            line1: formData.line1,
+            line2: formData.line2 || null,
+            city: formData.city,
+            district: formData.district || null,
+            state_code: formData.stateCode,
+            pincode: formData.pincode,
+          },
           phone: formData.phone,
           email: formData.email,
         }
       });
       console.log(result);
 
-      onAddClient(formData)
+      onAddClient({ ...formData, address: [formData.line1, formData.line2, formData.city].filter(Boolean).join(", ") })
       // Reset form
-      setFormData({
-        companyName: "",
-        ownerName: "",
-        gstNumber: "",
-        address: "",
-        pincode: "",
-        phone: "",
-        email: "",
-      })
+      setFormData(EMPTY_FORM)
       setErrors({})
     }
   }
 
   const handleCancel = () => {
-    setFormData({
-      companyName: "",
-      ownerName: "",
-      gstNumber: "",
-      address: "",
-      pincode: "",
-      phone: "",
-      email: "",
-    })
+    setFormData(EMPTY_FORM)
     setErrors({})
     onOpenChange(false)
   }
@@ -176,17 +199,41 @@ export function AddClientDialog({ open, onOpenChange, onAddClient }: AddClientDi
           </div>
 
           <div className="space-y-2">
-            <Label htmlFor="address">
+            <Label htmlFor="line1">
               Address <span className="text-destructive">*</span>
             </Label>
-            <Textarea
-              id="address"
-              value={formData.address}
-              onChange={(e) => handleInputChange("address", e.target.value)}
-              placeholder="Enter complete address"
-              rows={3}
+            <Input
+              id="line1"
+              value={formData.line1}
+              onChange={(e) => handleInputChange("line1", e.target.value)}
+              placeholder="Building, street"
+            />
+            <Input
+              id="line2"
+              value={formData.line2}
+              onChange={(e) => handleInputChange("line2", e.target.value)}
+              placeholder="Area, landmark (optional)"
             />
-            {errors.address && <p className="text-sm text-destructive">{errors.address}</p>}
+            {errors.line1 && <p className="text-sm text-destructive">{errors.line1}</p>}
+          </div>
+
+          <div className="grid grid-cols-2 gap-4">
+            <div className="space-y-2">
+              <Label htmlFor="city">
+                City <span className="text-destructive">*</span>
+              </Label>
+              <Input id="city" value={formData.city} onChange={(e) => handleInputChange("city", e.target.value)} />
+              {errors.city && <p className="text-sm text-destructive">{errors.city}</p>}
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="district">District (Optional)</Label>
+              <Input
+                id="district"
+                value={formData.district}
+                onChange={(e) => handleInputChange("district", e.target.value)}
+              />
+            </div>
           </div>
 
           <div className="grid grid-cols-2 gap-4">
@@ -197,13 +244,35 @@ export function AddClientDialog({ open, onOpenChange, onAddClient }: AddClientDi
               <Input
                 id="pincode"
                 value={formData.pincode}
-                onChange={(e) => handleInputChange("pincode", e.target.value)}
+                onChange={(e) => handlePincodeChange(e.target.value)}
                 placeholder="400001"
                 maxLength={6}
               />
               {errors.pincode && <p className="text-sm text-destructive">{errors.pincode}</p>}
             </div>
 
+            <div className="space-y-2">
+              <Label>
+                State <span className="text-destructive">*</span>
+              </Label>
+              <Select value={formData.stateCode} onValueChange={(value) => handleInputChange("stateCode", value)}>
+                <SelectTrigger>
+                  <SelectValue placeholder="Select state" />
+                </SelectTrigger>
+                <SelectContent>
+                  {indianStates.map((state) => (
+                    <SelectItem key={state.code} value={state.code}>
+                      {state.name} ({state.code})
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              {errors.stateCode && <p className="text-sm text-destructive">{errors.stateCode}</p>}
+            </div>
+          </div>
+
+          <div className="grid grid-cols-2 gap-4">
+
             <div className="space-y-2">
               <Label htmlFor="phone">
                 Phone Number <span className="text-destructive">*</span>
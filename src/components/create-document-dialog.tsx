@@ -33,6 +33,27 @@ interface LineItem {
   uqc: string // Unit Quantity Code
   rate: number
   amount: number
+  productId?: string // Set when picked from the catalog
+  // Carried over from templates, the dialog has no inputs for these yet
+  discount?: { kind: "percent" | "flat"; value: number } | null
+  cess?: { ad_valorem: number | null; specific: number | null } | null
+}
+
+interface AdditionalCharge {
+  description: string
+  amount: number
+  taxable?: boolean
+  hsn_code?: string | null
+  gst_rate?: number | null // Own GST rate, otherwise taxed at the invoice's rates
+}
+
+interface Product {
+  id: string
+  name: string
+  description?: string
+  hsn_code: string
+  unit: string
+  default_rate: number
 }
 
 interface Client {
@@ -45,45 +66,24 @@ interface Client {
   email: string
 }
 
-const indianStates = [
-  { code: "01", label: "Jammu and Kashmir (01)" },
-  { code: "02", label: "Himachal Pradesh (02)" },
-  { code: "03", label: "Punjab (03)" },
-  { code: "04", label: "Chandigarh (04)" },
-  { code: "05", label: "Uttarakhand (05)" },
-  { code: "06", label: "Haryana (06)" },
-  { code: "07", label: "Delhi (07)" },
-  { code: "08", label: "Rajasthan (08)" },
-  { code: "09", label: "Uttar Pradesh (09)" },
-  { code: "10", label: "Bihar (10)" },
-  { code: "11", label: "Sikkim (11)" },
-  { code: "12", label: "Arunachal Pradesh (12)" },
-  { code: "13", label: "Nagaland (13)" },
-  { code: "14", label: "Manipur (14)" },
-  { code: "15", label: "Mizoram (15)" },
-  { code: "16", label: "Tripura (16)" },
-  { code: "17", label: "Meghalaya (17)" },
-  { code: "18", label: "Assam (18)" },
-  { code: "19", label: "West Bengal (19)" },
-  { code: "20", label: "Jharkhand (20)" },
-  { code: "21", label: "Odisha (21)" },
-  { code: "22", label: "Chhattisgarh (22)" },
-  { code: "23", label: "Madhya Pradesh (23)" },
-  { code: "24", label: "Gujarat (24)" },
-  { code: "25", label: "Daman and Diu (25)" },
-  { code: "26", label: "Dadra and Nagar Haveli (26)" },
-  { code: "27", label: "Maharashtra (27)" },
-  { code: "28", label: "Andhra Pradesh (28)" },
-  { code: "29", label: "Karnataka (29)" },
-  { code: "30", label: "Goa (30)" },
-  { code: "31", label: "Lakshadweep (31)" },
-  { code: "32", label: "Kerala (32)" },
-  { code: "33", label: "Tamil Nadu (33)" },
-  { code: "34", label: "Puducherry (34)" },
-  { code: "35", label: "Andaman and Nicobar Islands (35)" },
-  { code: "36", label: "Telangana (36)" },
-  { code: "37", label: "Andhra Pradesh (New) (37)" },
-]
+interface BankAccount {
+  id: string
+  label: string
+  account_no: string
+  is_default: boolean
+}
+
+interface InvoiceTemplate {
+  id: string
+  name: string
+  company_id: string
+}
+
+interface IndianState {
+  code: string
+  name: string
+  union_territory: boolean
+}
 
 // Common UQC (Unit Quantity Code) options
 const UQC_OPTIONS = [
@@ -114,15 +114,22 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
 
   // Supply and reverse charge
   const [placeOfSupply, setPlaceOfSupply] = useState("27") // default Maharashtra (27)
+  const [indianStates, setIndianStates] = useState<IndianState[]>([])
   const [reverseCharge, setReverseCharge] = useState<"Y" | "N">("N")
 
   // Items and charges
   const [lineItems, setLineItems] = useState<LineItem[]>([
     { description: "", hsnCode: "", quantity: 1, uqc: "NOS", rate: 0, amount: 0 },
   ])
-  const [additionalCharges, setAdditionalCharges] = useState<{ description: string; amount: number }[]>([])
+  const [additionalCharges, setAdditionalCharges] = useState<AdditionalCharge[]>([])
   const [invoiceNumber, setInvoiceNumber] = useState<string>("")
 
+  const [templates, setTemplates] = useState<InvoiceTemplate[]>([])
+
+  // Catalog autocomplete for the item being typed
+  const [productSearch, setProductSearch] = useState<{ index: number; query: string } | null>(null)
+  const [products, setProducts] = useState<Product[]>([])
+
   // Client search state
   const [clientSearch, setClientSearch] = useState("")
   const [clients, setClients] = useState<Client[]>([])
@@ -137,6 +144,8 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
   const [igstRate, setIgstRate] = useState(18)
 
   const [profileDetails, setProfileDetails] = useState<object>({});
+  const [bankAccounts, setBankAccounts] = useState<BankAccount[]>([])
+  const [bankAccountId, setBankAccountId] = useState<string>("")
 
   const setUpDocumentFromProfile = async () => {
     const profile_data = await invoke<object>("get_profile_details");
@@ -178,12 +187,14 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
       quantity: item.quantity,
       unit: item.uqc,
       rate: item.rate,
-      amount: item.amount
+      amount: item.amount,
+      discount: item.discount ?? null,
+      cess: item.cess ?? null,
+      product_id: item.productId
     }));
 
     const transport_details = {
       transporter_name: transporterName,
-      place_of_supply: placeOfSupply, 
       vehicle_no: vehicleNo,
       station: station,
       eway_bill_no: eWayBillNo
@@ -216,11 +227,13 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
         cgst_percentage: cgstRate,
         sgst_percentage: sgstRate,
         igst_percentage: igstRate,
-        // additional_charges_json,
+        additional_charges: additionalCharges,
         total: total,
         reverse_charge: reverseCharge === "N" ? false : true, 
+        place_of_supply: placeOfSupply,
         transport_details,
-        bank_details
+        bank_details,
+        bank_account_id: bankAccountId || null
       }
     })
     console.log("Invoice created:", createInvoice)
@@ -229,8 +242,22 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
 
   useEffect(() => {
     setUpDocumentFromProfile();
+    invoke<IndianState[]>("get_states").then(setIndianStates);
+    invoke<InvoiceTemplate[]>("get_invoice_templates", { companyId: null }).then(setTemplates);
+    invoke<BankAccount[]>("get_bank_accounts").then((accounts) => {
+      setBankAccounts(accounts)
+      setBankAccountId(accounts.find((a) => a.is_default)?.id ?? "")
+    });
   }, [])
 
+  // Same state as the issuer's GSTIN is intra-state (CGST + SGST), anything else IGST
+  useEffect(() => {
+    const issuerState = profileDetails.gst_number?.slice(0, 2)
+    if (issuerState) {
+      setTaxType(placeOfSupply === issuerState ? "intrastate" : "interstate")
+    }
+  }, [placeOfSupply, profileDetails])
+
   // Search clients when search term changes
   useEffect(() => {
     const searchTimeout = setTimeout(async () => {
@@ -252,6 +279,90 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
     return () => clearTimeout(searchTimeout)
   }, [clientSearch])
 
+  // Search the catalog as an item description is typed
+  useEffect(() => {
+    const searchTimeout = setTimeout(async () => {
+      if (productSearch?.query.trim()) {
+        try {
+          setProducts(await invoke<Product[]>("search_products", { filter: { query: productSearch.query } }))
+        } catch (error) {
+          console.error("Error searching products:", error)
+        }
+      } else {
+        setProducts([])
+      }
+    }, 300)
+
+    return () => clearTimeout(searchTimeout)
+  }, [productSearch])
+
+  const selectProduct = (index: number, product: Product) => {
+    const updated = [...lineItems]
+    updated[index] = {
+      ...updated[index],
+      description: product.name,
+      hsnCode: product.hsn_code,
+      uqc: product.unit,
+      rate: product.default_rate,
+      amount: updated[index].quantity * product.default_rate,
+      productId: product.id,
+    }
+    setLineItems(updated)
+    setProductSearch(null)
+  }
+
+  // Pre-fill the dialog from a saved template, as a fresh draft numbered and dated for today
+  const applyTemplate = async (id: string) => {
+    try {
+      const draft = await invoke<any>("invoice_from_template", { id })
+      setInvoiceNumber(draft.invoice_number)
+      setDate(new Date(draft.invoice_date))
+      setSelectedClient({
+        id: draft.company_id,
+        name: draft.recipient_name,
+        owner_name: "",
+        gst_number: draft.recipient_gst_number,
+        address: draft.recipient_address,
+        phone: draft.recipient_phone,
+        email: draft.recipient_email ?? "",
+      })
+      setLineItems(
+        draft.items.map((item: any) => ({
+          description: item.description,
+          hsnCode: item.hsn_code,
+          quantity: item.quantity,
+          uqc: item.unit,
+          rate: item.rate,
+          amount: item.amount,
+          productId: item.product_id ?? undefined,
+          discount: item.discount,
+          cess: item.cess,
+        })),
+      )
+      setAdditionalCharges(
+        (draft.additional_charges ?? []).map((c: any) => ({
+          description: c.description,
+          amount: c.amount,
+          taxable: c.taxable,
+          hsn_code: c.hsn_code,
+          gst_rate: c.gst_rate,
+        })),
+      )
+      setCgstRate(draft.cgst_percentage ?? 0)
+      setSgstRate(draft.sgst_percentage ?? 0)
+      setIgstRate(draft.igst_percentage ?? 0)
+      setReverseCharge(draft.reverse_charge ? "Y" : "N")
+      if (draft.place_of_supply) setPlaceOfSupply(draft.place_of_supply)
+      setTransporterName(draft.transport_details.transporter_name || "SELF")
+      setVehicleNo(draft.transport_details.vehicle_no ?? "")
+      setStation(draft.transport_details.station ?? "")
+      setEWayBillNo("")
+      if (draft.bank_account_id) setBankAccountId(draft.bank_account_id)
+    } catch (error) {
+      console.error("Failed to load template:", error)
+    }
+  }
+
   const addLineItem = () => {
     setLineItems([...lineItems, { description: "", hsnCode: "", quantity: 1, uqc: "NOS", rate: 0, amount: 0 }])
   }
@@ -265,6 +376,10 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
   const updateLineItem = (index: number, field: keyof LineItem, value: string | number) => {
     const updated = [...lineItems]
     updated[index] = { ...updated[index], [field]: value }
+    // A catalog item keeps its HSN code, editing either makes it a free-text line
+    if (field === "description" || field === "hsnCode") {
+      updated[index].productId = undefined
+    }
 
     if (field === "quantity" || field === "rate") {
       updated[index].amount = updated[index].quantity * updated[index].rate
@@ -319,6 +434,23 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
                 onChange={(e) => setInvoiceNumber(e.target.value)}
                 placeholder={`Enter Invoice Number`} />
             </div>
+            {templates.length > 0 && (
+              <div className="space-y-2">
+                <Label>From Template</Label>
+                <Select onValueChange={applyTemplate}>
+                  <SelectTrigger>
+                    <SelectValue placeholder="Start from a template" />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {templates.map((t) => (
+                      <SelectItem key={t.id} value={t.id}>
+                        {t.name}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+            )}
           </div>
 
           <div className="space-y-2">
@@ -438,6 +570,25 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
             </div>
           </div>
 
+          {/* Bank account the client pays into */}
+          {bankAccounts.length > 0 && (
+            <div className="space-y-2">
+              <Label>Bank Account</Label>
+              <Select value={bankAccountId} onValueChange={setBankAccountId}>
+                <SelectTrigger>
+                  <SelectValue placeholder="Select bank account" />
+                </SelectTrigger>
+                <SelectContent>
+                  {bankAccounts.map((a) => (
+                    <SelectItem key={a.id} value={a.id}>
+                      {a.label} (xx{a.account_no.slice(-4)})
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+          )}
+
           {/* Place of Supply + Reverse Charge */}
           <div className="grid grid-cols-2 gap-4">
             <div className="space-y-2">
@@ -449,7 +600,7 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
                 <SelectContent>
                   {indianStates.map((s) => (
                     <SelectItem key={s.code} value={s.code}>
-                      {s.label}
+                      {s.name} ({s.code})
                     </SelectItem>
                   ))}
                 </SelectContent>
@@ -497,12 +648,34 @@ export function CreateInvoiceDialog({ children }: { children: React.ReactNode })
             <div className="space-y-3">
               {lineItems.map((item, index) => (
                 <div key={index} className="grid grid-cols-12 gap-2 items-center">
-                  <div className="col-span-4">
+                  <div className="col-span-4 relative">
                     <Input
                       placeholder="Item description"
                       value={item.description}
-                      onChange={(e) => updateLineItem(index, "description", e.target.value)}
+                      onChange={(e) => {
+                        updateLineItem(index, "description", e.target.value)
+                        setProductSearch({ index, query: e.target.value })
+                      }}
+                      onBlur={() => setTimeout(() => setProductSearch(null), 150)}
                     />
+                    {productSearch?.index === index && products.length > 0 && (
+                      <div className="absolute z-10 mt-1 w-full rounded-md border bg-popover shadow-md">
+                        {products.map((product) => (
+                          <button
+                            key={product.id}
+                            type="button"
+                            className="flex w-full justify-between px-3 py-2 text-left text-sm hover:bg-accent"
+                            onMouseDown={(e) => e.preventDefault()}
+                            onClick={() => selectProduct(index, product)}
+                          >
+                            <span>{product.name}</span>
+                            <span className="text-muted-foreground">
+                              {product.hsn_code} · ₹{product.default_rate}
+                            </span>
+                          </button>
+                        ))}
+                      </div>
+                    )}
                   </div>
                   <div className="col-span-2">
                     <Input
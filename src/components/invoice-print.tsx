@@ -1,5 +1,7 @@
 "use client"
 
+import { useState, useEffect } from "react"
+import { invoke } from "@tauri-apps/api/core"
 import { Button } from "@/components/ui/button"
 import { Download, Printer } from 'lucide-react'
 
@@ -15,6 +17,9 @@ interface InvoiceItem {
 interface AdditionalCharge {
   description: string
   amount: number
+  taxable?: boolean
+  hsn_code?: string
+  gst_rate?: number
 }
 
 interface InvoiceData {
@@ -36,12 +41,29 @@ interface InvoiceData {
     gstNumber: string
     email: string
   }
+  shipTo?: {
+    name: string
+    address: string
+    gstNumber?: string
+  } // Goods go to the billing address when missing
   items: InvoiceItem[]
   additionalCharges?: AdditionalCharge[]
   taxType: "interstate" | "intrastate"
   cgstRate: number
   sgstRate: number
   igstRate: number
+  discount?: number // Line and invoice discounts together, taken off before tax
+  // Tax figures worked out by the backend, which knows each charge's own rate
+  taxableValue?: number
+  nonTaxableCharges?: number
+  cgst?: number
+  sgst?: number
+  igst?: number
+  cess?: number
+  tcsRate?: number
+  tcs?: number
+  roundOff?: number // Worked out and stored by the backend
+  total?: number
   notes?: string
   transport?: {
     name: string
@@ -50,6 +72,10 @@ interface InvoiceData {
     eWayBillNo: string
   }
   placeOfSupply: string
+  supplyType?: string // regular, sez_with_payment, export_without_payment, ...
+  lutReference?: string
+  currency?: { code: string; exchange_rate: number }
+  totalInr?: number
   reverseCharge: string
   irn?: string
   ackNo?: string
@@ -62,6 +88,15 @@ interface InvoiceData {
   }
 }
 
+// Printed under the title for zero-rated and deemed export supplies
+const SUPPLY_DECLARATIONS: Record<string, string | undefined> = {
+  sez_with_payment: "SUPPLY MEANT FOR SEZ ON PAYMENT OF IGST",
+  sez_without_payment: "SUPPLY MEANT FOR SEZ UNDER LUT/BOND WITHOUT PAYMENT OF IGST",
+  export_with_payment: "SUPPLY MEANT FOR EXPORT ON PAYMENT OF IGST",
+  export_without_payment: "SUPPLY MEANT FOR EXPORT UNDER LUT/BOND WITHOUT PAYMENT OF IGST",
+  deemed_export: "DEEMED EXPORT",
+}
+
 interface InvoicePrintProps {
   invoiceData: InvoiceData
 }
@@ -168,74 +203,44 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
   };
 
   const itemsSubtotal = invoiceData.items.reduce((sum, item) => sum + item.amount, 0)
-  const additionalChargesTotal = invoiceData.additionalCharges?.reduce((sum, charge) => sum + charge.amount, 0) || 0
-  const subtotal = itemsSubtotal + additionalChargesTotal
-
-  const cgst = invoiceData.taxType === "intrastate" ? (subtotal * invoiceData.cgstRate) / 100 : 0
-  const sgst = invoiceData.taxType === "intrastate" ? (subtotal * invoiceData.sgstRate) / 100 : 0
-  const igst = invoiceData.taxType === "interstate" ? (subtotal * invoiceData.igstRate) / 100 : 0
-
-  const totalTax = cgst + sgst + igst
-  const total = Math.round(subtotal + totalTax)
-
-  const ones = [
-    "",
-    "One",
-    "Two",
-    "Three",
-    "Four",
-    "Five",
-    "Six",
-    "Seven",
-    "Eight",
-    "Nine",
-    "Ten",
-    "Eleven",
-    "Twelve",
-    "Thirteen",
-    "Fourteen",
-    "Fifteen",
-    "Sixteen",
-    "Seventeen",
-    "Eighteen",
-    "Nineteen",
-  ]
-  const tens = ["", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety"]
-
-  const numberToWords = (num: number): string => {
-    const numToString = (n: number): string => {
-      if (n === 0) return "";
-      return " " + numberToWords(n);
-    }
-
-    if (num === 0) return "Zero";
+  const charges = invoiceData.additionalCharges ?? []
+  const taxableCharges = charges.filter((charge) => charge.taxable !== false).reduce((sum, charge) => sum + charge.amount, 0)
+  const nonTaxableCharges =
+    invoiceData.nonTaxableCharges ??
+    charges.filter((charge) => charge.taxable === false).reduce((sum, charge) => sum + charge.amount, 0)
+  const discount = invoiceData.discount ?? 0
+  const subtotal = invoiceData.taxableValue ?? itemsSubtotal - discount + taxableCharges
 
-    if (num < 0) return "Negative" + numToString(Math.abs(num));
+  const cgst = invoiceData.cgst ?? (invoiceData.taxType === "intrastate" ? (subtotal * invoiceData.cgstRate) / 100 : 0)
+  const sgst = invoiceData.sgst ?? (invoiceData.taxType === "intrastate" ? (subtotal * invoiceData.sgstRate) / 100 : 0)
+  const igst = invoiceData.igst ?? (invoiceData.taxType === "interstate" ? (subtotal * invoiceData.igstRate) / 100 : 0)
 
-    if (num >= 10000000) {
-      return numberToWords(Math.floor(num / 10000000)) + " Crore" + numToString(num % 10000000);
-    }
+  const cess = invoiceData.cess ?? 0
+  const tcs = invoiceData.tcs ?? 0
 
-    if (num >= 100000) {
-      return numberToWords(Math.floor(num / 100000)) + " Lakh" + numToString(num % 100000);
-    }
+  const totalTax = cgst + sgst + igst + cess
+  const roundOff = invoiceData.roundOff ?? 0
+  const total = invoiceData.total ?? subtotal + totalTax + nonTaxableCharges + tcs + roundOff
 
-    if (num >= 1000) {
-      return numberToWords(Math.floor(num / 1000)) + " Thousand" + numToString(num % 1000);
-    }
+  const totalInRupees = invoiceData.totalInr ?? total
+  const supplyDeclaration = SUPPLY_DECLARATIONS[invoiceData.supplyType ?? "regular"]
 
-    if (num >= 100) {
-      return numberToWords(Math.floor(num / 100)) + " Hundred" + numToString(num % 100);
-    }
+  const [totalInWords, setTotalInWords] = useState("")
 
-    if (num >= 20) {
-      return tens[Math.floor(num / 10)] + numToString(num % 10);
-    }
+  useEffect(() => {
+    invoke<string>("amount_to_words", { amount: totalInRupees })
+      .then(setTotalInWords)
+      .catch((error) => console.error("Failed to convert amount to words:", error))
+  }, [totalInRupees])
 
-    return ones[num];
-  }
+  // Left out when there's no UPI ID on the profile or the invoice isn't payable in rupees
+  const [upiQr, setUpiQr] = useState<string | null>(null)
 
-  const totalInWords = numberToWords(total) + " Only"
+  useEffect(() => {
+    invoke<number[]>("get_invoice_upi_qr", { invoiceId: invoiceData.id, format: "svg" })
+      .then((bytes) => setUpiQr(new TextDecoder().decode(new Uint8Array(bytes))))
+      .catch(() => setUpiQr(null))
+  }, [invoiceData.id])
 
   const MAX_ITEMS_DISPLAY = 15
   const displayItems = Array(MAX_ITEMS_DISPLAY)
@@ -267,6 +272,12 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
           {/* Header Section */}
           <div className="text-center border-b border-black pb-2 px-2 flex-shrink-0">
             <div className="text-base font-bold mb-1">TAX INVOICE</div>
+            {supplyDeclaration && (
+              <div className="text-xs font-bold mb-1">
+                {supplyDeclaration}
+                {invoiceData.lutReference ? ` (LUT/Bond: ${invoiceData.lutReference})` : ""}
+              </div>
+            )}
             <div className="text-sm font-bold mb-1">{invoiceData.issuer.name}</div>
             <div className="text-xs mb-0.5">{invoiceData.issuer.address}</div>
             <div className="text-xs mb-0.5">
@@ -298,6 +309,15 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
                 <span className="mr-1">:</span>
                 <span>{invoiceData.reverseCharge}</span>
               </div>
+              {invoiceData.currency && (
+                <div className="flex">
+                  <span className="w-20">Currency</span>
+                  <span className="mr-1">:</span>
+                  <span>
+                    {invoiceData.currency.code} @ ₹{invoiceData.currency.exchange_rate}
+                  </span>
+                </div>
+              )}
             </div>
 
             <div className="p-1.5">
@@ -335,9 +355,9 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
 
             <div className="p-1.5">
               <div className="font-bold mb-0.5">Shipped to :</div>
-              <div className="font-bold text-xs">{invoiceData.recipient.name}</div>
-              <div className="whitespace-pre-line text-xs mb-0.5">{invoiceData.recipient.address}</div>
-              <div className="text-xs">GSTIN / UIN : {invoiceData.recipient.gstNumber}</div>
+              <div className="font-bold text-xs">{(invoiceData.shipTo ?? invoiceData.recipient).name}</div>
+              <div className="whitespace-pre-line text-xs mb-0.5">{(invoiceData.shipTo ?? invoiceData.recipient).address}</div>
+              <div className="text-xs">GSTIN / UIN : {(invoiceData.shipTo ?? invoiceData.recipient).gstNumber}</div>
             </div>
           </div>
 
@@ -387,6 +407,18 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
                   </tr>
                 ))}
 
+                {discount > 0 && (
+                  <tr className="border-t border-black">
+                    <td colSpan={4} className="p-1"></td>
+                    <td className="p-1 text-left text-xs border-r border-black">
+                      Less : Discount
+                    </td>
+                    <td className="p-1 text-right text-xs">
+                      {discount.toLocaleString("en-IN", { minimumFractionDigits: 2 })}
+                    </td>
+                  </tr>
+                )}
+
                 {/* Subtotal Row */}
                 <tr className="border-t border-black">
                   <td colSpan={4} className="p-1"></td>
@@ -438,6 +470,54 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
                   </>
                 )}
 
+                {cess > 0 && (
+                  <tr>
+                    <td colSpan={4} className="p-1"></td>
+                    <td className="p-1 text-left text-xs border-r border-black">
+                      Add : Cess
+                    </td>
+                    <td className="p-1 text-right text-xs">
+                      {cess.toLocaleString("en-IN", { minimumFractionDigits: 2 })}
+                    </td>
+                  </tr>
+                )}
+
+                {nonTaxableCharges > 0 && (
+                  <tr>
+                    <td colSpan={4} className="p-1"></td>
+                    <td className="p-1 text-left text-xs border-r border-black">
+                      Add : Non-taxable Charges
+                    </td>
+                    <td className="p-1 text-right text-xs">
+                      {nonTaxableCharges.toLocaleString("en-IN", { minimumFractionDigits: 2 })}
+                    </td>
+                  </tr>
+                )}
+
+                {tcs > 0 && (
+                  <tr>
+                    <td colSpan={4} className="p-1"></td>
+                    <td className="p-1 text-left text-xs border-r border-black">
+                      Add : TCS @ {invoiceData.tcsRate} %
+                    </td>
+                    <td className="p-1 text-right text-xs">
+                      {tcs.toLocaleString("en-IN", { minimumFractionDigits: 2 })}
+                    </td>
+                  </tr>
+                )}
+
+                {roundOff !== 0 && (
+                  <tr>
+                    <td colSpan={4} className="p-1"></td>
+                    <td className="p-1 text-left text-xs border-r border-black">
+                      {roundOff > 0 ? "Add" : "Less"} : Round Off
+                    </td>
+                    <td className="p-1 text-right text-xs">
+                      {Math.abs(roundOff).toLocaleString("en-IN", { minimumFractionDigits: 2 })}
+                    </td>
+                  </tr>
+                )}
+
                 {/* Grand Total Row */}
                 <tr className="border-t border-black">
                   <td colSpan={4} className="p-1"></td>
@@ -454,8 +534,19 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
 
           {/* Amount in Words */}
           <div className="text-xs border-b border-black p-1.5 flex-shrink-0">
-            <span>Amount in Rupees: </span>
-            <span className="font-bold">Rupees {totalInWords}</span>
+            {invoiceData.currency ? (
+              <>
+                <span>Rupee equivalent: </span>
+                <span className="font-bold">
+                  ₹{totalInRupees.toLocaleString("en-IN", { minimumFractionDigits: 2 })} ({totalInWords})
+                </span>
+              </>
+            ) : (
+              <>
+                <span>Amount in Rupees: </span>
+                <span className="font-bold">{totalInWords}</span>
+              </>
+            )}
           </div>
 
           {/* Bank Details and Terms */}
@@ -473,6 +564,18 @@ export function InvoicePrint({ invoiceData }: InvoicePrintProps) {
                 </div>
               )}
             </div>
+            <div className="p-1.5 flex items-center gap-2">
+              {upiQr && (
+                <>
+                  <img
+                    src={`data:image/svg+xml;utf8,${encodeURIComponent(upiQr)}`}
+                    alt="UPI payment QR code"
+                    className="w-20 h-20"
+                  />
+                  <div className="font-bold">Scan to pay with any UPI app</div>
+                </>
+              )}
+            </div>
           </div>
 
           {/* Footer */}
@@ -75,6 +75,7 @@ export function OnboardingForm() {
     accountNumber: "",
     ifscCode: "",
     branchName: "",
+    upiId: "",
 
     invoicePrefix: "INV",
     nextInvoiceNumber: 1,
@@ -142,6 +143,9 @@ export function OnboardingForm() {
           newErrors.ifscCode = "Invalid IFSC code format"
         }
         if (!formData.branchName.trim()) newErrors.branchName = "Branch name is required"
+        if (formData.upiId.trim() && !/^[A-Za-z0-9._-]{2,256}@[A-Za-z0-9]{2,64}$/.test(formData.upiId.trim())) {
+          newErrors.upiId = "Invalid UPI ID, e.g. yourshop@okhdfcbank"
+        }
         break
     }
 
@@ -183,6 +187,7 @@ export function OnboardingForm() {
           bank_ifsc: formData.ifscCode,
           bank_account_name: formData.accountName,
           bank_account_number: formData.accountNumber,
+          upi_id: formData.upiId.trim() || null,
           invoice_prefix: formData.invoicePrefix,
           next_invoice_number: Number(formData.nextInvoiceNumber),
           next_debit_number: Number(formData.nextDebitNumber),
@@ -449,6 +454,21 @@ export function OnboardingForm() {
                 {errors.ifscCode && <p className="text-sm text-red-600 dark:text-red-400">{errors.ifscCode}</p>}
               </div>
 
+              <div className="space-y-2">
+                <Label htmlFor="upiId" className="text-sm font-medium text-gray-700 dark:text-gray-300">
+                  UPI ID
+                </Label>
+                <Input
+                  id="upiId"
+                  placeholder="yourshop@okhdfcbank"
+                  value={formData.upiId}
+                  onChange={(e) => handleInputChange("upiId", e.target.value)}
+                  className="h-11 font-mono border-gray-200 dark:border-gray-700 focus:border-indigo-500 dark:focus:border-indigo-400 transition-colors"
+                />
+                <p className="text-xs text-gray-500 dark:text-gray-400">Invoices show a QR code customers can scan to pay</p>
+                {errors.upiId && <p className="text-sm text-red-600 dark:text-red-400">{errors.upiId}</p>}
+              </div>
+
               <div className="border-t pt-4 mt-6">
                 <div className="flex items-center space-x-2 text-gray-700 dark:text-gray-300 mb-4">
                   <FileText className="w-5 h-5" />
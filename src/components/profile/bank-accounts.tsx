@@ -0,0 +1,234 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Checkbox } from "@/components/ui/checkbox"
+import { Badge } from "@/components/ui/badge"
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Alert, AlertDescription } from "@/components/ui/alert"
+import { Landmark, Plus, Edit, Trash2 } from "lucide-react"
+import { invoke } from "@tauri-apps/api/core"
+
+interface BankAccount {
+  id: string | null
+  label: string
+  bank_name: string
+  branch: string
+  account_name: string
+  account_no: string
+  ifsc_code: string
+  is_default: boolean
+}
+
+interface IfscLookup {
+  ifsc_code: string
+  bank_code: string
+  bank_name: string | null
+  branch_code: string
+}
+
+const emptyAccount: BankAccount = {
+  id: null,
+  label: "",
+  bank_name: "",
+  branch: "",
+  account_name: "",
+  account_no: "",
+  ifsc_code: "",
+  is_default: false,
+}
+
+// Lives inside the profile form, so its buttons must not submit it
+export function BankAccounts() {
+  const [accounts, setAccounts] = useState<BankAccount[]>([])
+  const [editing, setEditing] = useState<BankAccount | null>(null)
+  const [ifscHint, setIfscHint] = useState("")
+  const [error, setError] = useState("")
+
+  const loadAccounts = async () => {
+    try {
+      setAccounts(await invoke<BankAccount[]>("get_bank_accounts"))
+    } catch (error) {
+      setError(String(error))
+    }
+  }
+
+  useEffect(() => {
+    loadAccounts()
+  }, [])
+
+  const handleIfscChange = async (value: string) => {
+    const ifsc = value.toUpperCase()
+    setEditing((current) => current && { ...current, ifsc_code: ifsc })
+    setIfscHint("")
+    if (ifsc.length !== 11) return
+
+    // Only the bank is known from the IFSC, the branch still has to be typed in
+    try {
+      const found = await invoke<IfscLookup>("lookup_ifsc", { ifsc })
+      if (found.bank_name) {
+        setEditing((current) => current && { ...current, bank_name: current.bank_name || found.bank_name! })
+        setIfscHint(`${found.bank_name}, branch code ${found.branch_code}`)
+      } else {
+        setIfscHint(`Bank code ${found.bank_code} is not in the list, enter the bank name`)
+      }
+    } catch (error) {
+      setIfscHint(String(error))
+    }
+  }
+
+  const handleSave = async () => {
+    if (!editing) return
+    try {
+      await invoke("save_bank_account", { account: editing })
+      setEditing(null)
+      setIfscHint("")
+      setError("")
+      await loadAccounts()
+    } catch (error) {
+      setError(String(error))
+    }
+  }
+
+  const handleDelete = async (account: BankAccount) => {
+    if (!account.id || !confirm(`Delete ${account.label}? Invoices already issued keep its details.`)) return
+    try {
+      await invoke("delete_bank_account", { id: account.id })
+      setError("")
+      await loadAccounts()
+    } catch (error) {
+      setError(String(error))
+    }
+  }
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Landmark className="h-5 w-5" />
+          Bank Accounts
+        </CardTitle>
+        <CardDescription>Invoices show the default account unless another one is picked</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        {error && (
+          <Alert variant="destructive">
+            <AlertDescription>{error}</AlertDescription>
+          </Alert>
+        )}
+
+        {accounts.map((account) => (
+          <div key={account.id} className="flex items-center justify-between rounded-md border p-3">
+            <div className="space-y-1">
+              <div className="flex items-center gap-2 font-medium">
+                {account.label}
+                {account.is_default && <Badge variant="secondary">Default</Badge>}
+              </div>
+              <p className="text-sm text-muted-foreground font-mono">
+                {account.account_no} · {account.ifsc_code} · {account.branch}
+              </p>
+            </div>
+            <div className="flex gap-2">
+              <Button type="button" variant="ghost" size="sm" onClick={() => setEditing(account)}>
+                <Edit className="h-4 w-4" />
+              </Button>
+              <Button type="button" variant="ghost" size="sm" onClick={() => handleDelete(account)}>
+                <Trash2 className="h-4 w-4" />
+              </Button>
+            </div>
+          </div>
+        ))}
+
+        {editing ? (
+          <div className="space-y-4 rounded-md border p-4">
+            <div className="grid grid-cols-2 gap-4">
+              <div className="space-y-2">
+                <Label htmlFor="accountLabel">Label</Label>
+                <Input
+                  id="accountLabel"
+                  value={editing.label}
+                  placeholder="e.g. Current account"
+                  onChange={(e) => setEditing({ ...editing, label: e.target.value })}
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="accountIfsc">IFSC Code *</Label>
+                <Input
+                  id="accountIfsc"
+                  value={editing.ifsc_code}
+                  onChange={(e) => handleIfscChange(e.target.value)}
+                  className="font-mono"
+                  maxLength={11}
+                />
+                {ifscHint && <p className="text-sm text-muted-foreground">{ifscHint}</p>}
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="accountBank">Bank Name *</Label>
+                <Input
+                  id="accountBank"
+                  value={editing.bank_name}
+                  onChange={(e) => setEditing({ ...editing, bank_name: e.target.value })}
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="accountBranch">Branch Name</Label>
+                <Input
+                  id="accountBranch"
+                  value={editing.branch}
+                  onChange={(e) => setEditing({ ...editing, branch: e.target.value })}
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="accountHolder">Account Name *</Label>
+                <Input
+                  id="accountHolder"
+                  value={editing.account_name}
+                  onChange={(e) => setEditing({ ...editing, account_name: e.target.value })}
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="accountNo">Account Number *</Label>
+                <Input
+                  id="accountNo"
+                  value={editing.account_no}
+                  onChange={(e) => setEditing({ ...editing, account_no: e.target.value })}
+                  className="font-mono"
+                />
+              </div>
+            </div>
+            <div className="flex items-center gap-2">
+              <Checkbox
+                id="accountDefault"
+                checked={editing.is_default}
+                onCheckedChange={(checked) => setEditing({ ...editing, is_default: checked === true })}
+              />
+              <Label htmlFor="accountDefault">Use on new invoices by default</Label>
+            </div>
+            <div className="flex justify-end gap-2">
+              <Button
+                type="button"
+                variant="outline"
+                onClick={() => {
+                  setEditing(null)
+                  setIfscHint("")
+                }}
+              >
+                Cancel
+              </Button>
+              <Button type="button" onClick={handleSave}>
+                Save Account
+              </Button>
+            </div>
+          </div>
+        ) : (
+          <Button type="button" variant="outline" onClick={() => setEditing(emptyAccount)}>
+            <Plus className="mr-2 h-4 w-4" />
+            Add Bank Account
+          </Button>
+        )}
+      </CardContent>
+    </Card>
+  )
+}
@@ -13,6 +13,7 @@ import { Textarea } from "@/components/ui/textarea"
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select"
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs"
 import { invoke } from "@tauri-apps/api/core"
+import { BankAccounts } from "@/components/profile/bank-accounts"
 
 const indianStates = [
   { code: "01", name: "Jammu and Kashmir" },
@@ -76,6 +77,7 @@ export function ProfileForm() {
     accountNumber: "",
     ifscCode: "",
     branchName: "",
+    upiId: "",
 
     invoicePrefix: "",
     nextInvoiceNumber: 1,
@@ -112,6 +114,7 @@ export function ProfileForm() {
         accountNumber: profileDetails.bank_account_number,
         ifscCode: profileDetails.bank_ifsc,
         branchName: profileDetails.bank_branch,
+        upiId: profileDetails.upi_id ?? "",
 
         invoicePrefix: profileDetails.invoice_prefix,
         nextInvoiceNumber: profileDetails.next_invoice_number,
@@ -179,6 +182,9 @@ export function ProfileForm() {
       newErrors.ifscCode = "Invalid IFSC code format"
     }
     if (!formData.branchName.trim()) newErrors.branchName = "Branch name is required"
+    if (formData.upiId.trim() && !/^[A-Za-z0-9._-]{2,256}@[A-Za-z0-9]{2,64}$/.test(formData.upiId.trim())) {
+      newErrors.upiId = "Invalid UPI ID, e.g. yourshop@okhdfcbank"
+    }
 
     setErrors(newErrors)
     return Object.keys(newErrors).length === 0
@@ -419,6 +425,18 @@ export function ProfileForm() {
                   {errors.ifscCode && <p className="text-sm text-destructive">{errors.ifscCode}</p>}
                 </div>
 
+                <div className="space-y-2">
+                  <Label htmlFor="upiId">UPI ID</Label>
+                  <Input
+                    id="upiId"
+                    value={formData.upiId}
+                    onChange={(e) => handleInputChange("upiId", e.target.value)}
+                    className="font-mono"
+                  />
+                  <p className="text-sm text-muted-foreground">Invoices show a QR code customers can scan to pay</p>
+                  {errors.upiId && <p className="text-sm text-destructive">{errors.upiId}</p>}
+                </div>
+
                 <div className="border-t pt-4 mt-6">
                   <div className="flex items-center gap-2 mb-4">
                     <FileText className="h-5 w-5" />
@@ -461,6 +479,8 @@ export function ProfileForm() {
                 </div>
               </CardContent>
             </Card>
+
+            <BankAccounts />
           </TabsContent>
         </Tabs>
 